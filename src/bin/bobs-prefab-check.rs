@@ -0,0 +1,48 @@
+//! Check prefab files for errors, see [bevy_bobs::prefab::validate]
+
+use std::process::ExitCode;
+
+use bevy_bobs::prefab::validate::{check_files, DynamicPrefab};
+
+const USAGE: &str = "usage: bobs-prefab-check [--assets <dir>] [--refs <file>]... <file>...
+
+Checks that prefab files parse and that all cross references are valid.
+
+options:
+    --assets <dir>  asset directory used to check sound paths
+    --refs <file>   extra prefab file whose ids may be referenced";
+
+fn main() -> ExitCode {
+    let mut asset_dir = None;
+    let mut ref_files = vec![];
+    let mut files = vec![];
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--assets" => asset_dir = args.next(),
+            "--refs" => ref_files.extend(args.next()),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            },
+            _ => files.push(arg),
+        }
+    }
+    if files.is_empty() {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    }
+
+    let errors = check_files::<DynamicPrefab, _>(&files, &ref_files, asset_dir.as_deref());
+    if errors.is_empty() {
+        println!("checked {} files", files.len());
+        ExitCode::SUCCESS
+    } else {
+        for error in errors.iter() {
+            eprintln!("error: {}", error);
+        }
+        eprintln!("found {} errors", errors.len());
+        ExitCode::FAILURE
+    }
+}
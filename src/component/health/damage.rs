@@ -0,0 +1,128 @@
+//! Damage types and the modifiers that reduce incoming damage
+//!
+//! Damage dealt through a [DamageEvent](super::DamageEvent) first has the target's [Resistances]
+//! applied, after which [Armor] reduces physical damage by a flat amount.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+#[cfg(feature = "serde")]
+use bevy::reflect::ReflectDeserialize;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::HealthValue;
+
+/// Type of damage, used to look up resistances
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DamageType {
+    #[default]
+    Physical,
+    Fire,
+    Ice,
+    Poison,
+    Magic,
+    /// Damage that ignores resistances and armor
+    Pure,
+    /// Game specific damage type
+    Custom(u32),
+}
+
+/// Percentage of damage resisted for each damage type
+///
+/// A resistance of `0.25` means 25% less damage is taken, while a negative resistance is a
+/// weakness that increases damage taken. Resistances above `1.0` are treated as full immunity.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Component, Reflect, Debug, Default, Clone)]
+#[cfg_attr(not(feature = "serde"), reflect_value(Component))]
+#[cfg_attr(feature = "serde", reflect_value(Component, Deserialize))]
+pub struct Resistances {
+    resistances: HashMap<DamageType, f32>,
+}
+
+impl Resistances {
+    pub fn new() -> Self {
+        Resistances::default()
+    }
+
+    /// Set the resistance to a damage type
+    pub fn with(mut self, damage_type: DamageType, resistance: f32) -> Self {
+        self.set(damage_type, resistance);
+        self
+    }
+
+    /// Set the resistance to a damage type
+    pub fn set(&mut self, damage_type: DamageType, resistance: f32) {
+        self.resistances.insert(damage_type, resistance);
+    }
+
+    /// Get the resistance to a damage type, defaulting to zero
+    pub fn get(&self, damage_type: DamageType) -> f32 {
+        self.resistances.get(&damage_type).copied().unwrap_or(0.)
+    }
+}
+
+/// Flat reduction to physical damage
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+#[derive(Component, Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(feature = "serde"), reflect_value(Component))]
+#[cfg_attr(feature = "serde", reflect_value(Component, Deserialize))]
+pub struct Armor<T: HealthValue = u32>(pub T);
+
+/// How incoming damage was modified before being applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DamageBreakdown<T: HealthValue = u32> {
+    pub damage_type: DamageType,
+    /// Damage before any modifiers
+    pub base: T,
+    /// Damage after resistances were applied
+    pub resisted: T,
+    /// Damage blocked by armor
+    pub armor_blocked: T,
+    /// Damage absorbed by the target's shield
+    pub shield_absorbed: T,
+    /// Damage that was taken off the target's health after shields
+    pub final_amount: T,
+}
+
+impl<T: HealthValue> DamageBreakdown<T> {
+    /// Apply resistances and armor to an amount of damage
+    ///
+    /// Shields are not taken into account, so `final_amount` is the damage that will be dealt to
+    /// the target's [Health](super::Health) as a whole.
+    pub fn compute(
+        amount: T,
+        damage_type: DamageType,
+        resistances: Option<&Resistances>,
+        armor: Option<&Armor<T>>,
+    ) -> Self {
+        let mut breakdown = DamageBreakdown {
+            damage_type,
+            base: amount,
+            resisted: amount,
+            armor_blocked: T::zero(),
+            shield_absorbed: T::zero(),
+            final_amount: amount,
+        };
+        if damage_type == DamageType::Pure {
+            return breakdown;
+        }
+
+        if let Some(resistances) = resistances {
+            let multiplier = (1. - resistances.get(damage_type)).max(0.);
+            breakdown.resisted = amount.mul_f32(multiplier);
+        }
+        if let (DamageType::Physical, Some(armor)) = (damage_type, armor) {
+            breakdown.armor_blocked = armor.0.min(breakdown.resisted);
+        }
+        breakdown.final_amount = breakdown.resisted.saturating_sub(breakdown.armor_blocked);
+        breakdown
+    }
+
+    /// Damage dealt to both the target's shield and health
+    pub fn dealt(&self) -> T {
+        self.shield_absorbed.saturating_add(self.final_amount)
+    }
+}
@@ -0,0 +1,263 @@
+//! Damage and healing over time
+//!
+//! Effects such as poison, burning or healing auras are applied with an [ApplyEffect] event. Each
+//! tick of an effect sends a [DamageEvent] or [HealEvent], so resistances, invulnerability and
+//! death detection all apply as usual.
+//!
+//! ```ignore
+//! writer.send(ApplyEffect {
+//!     target,
+//!     effect: HealthEffect::damage("poison", DamageType::Poison, 2, 0.5, 5.)
+//!         .with_stacking(Stacking::Refresh),
+//! });
+//! ```
+
+use std::collections::HashMap;
+
+use bevy::{ecs::entity::Entities, prelude::*};
+
+use super::{damage::DamageType, DamageEvent, HealEvent, HealthValue};
+
+/// What an effect does every tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EffectKind {
+    Damage(DamageType),
+    Heal,
+}
+
+/// How an effect is applied when an effect with the same name is already active
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stacking {
+    /// Run alongside the existing effects, removing the oldest when there are more than `max`
+    Stack { max: usize },
+    /// Restart the duration of the existing effect
+    Refresh,
+    /// Remove the existing effect
+    Replace,
+    /// Add the amount to the existing effect and restart its duration
+    Intensify,
+}
+
+/// Shortest interval between ticks of a [HealthEffect] in seconds
+pub const MIN_EFFECT_INTERVAL: f32 = 0.01;
+
+/// Effect that changes health every interval for a duration
+#[derive(Debug, Clone)]
+pub struct HealthEffect<T: HealthValue = u32> {
+    /// Used to find effects of the same kind when stacking
+    pub name: String,
+    pub kind: EffectKind,
+    /// Amount of health changed each tick
+    pub amount: T,
+    pub stacking: Stacking,
+    /// Entity responsible for the effect, if any
+    pub source: Option<Entity>,
+    tick: Timer,
+    duration: Timer,
+}
+
+impl<T: HealthValue> HealthEffect<T> {
+    /// Effect that deals damage every `interval` seconds for `duration` seconds
+    pub fn damage(
+        name: &str,
+        damage_type: DamageType,
+        amount: T,
+        interval: f32,
+        duration: f32,
+    ) -> Self {
+        HealthEffect::new(
+            name,
+            EffectKind::Damage(damage_type),
+            amount,
+            interval,
+            duration,
+        )
+    }
+
+    /// Effect that heals every `interval` seconds for `duration` seconds
+    pub fn heal(name: &str, amount: T, interval: f32, duration: f32) -> Self {
+        HealthEffect::new(name, EffectKind::Heal, amount, interval, duration)
+    }
+
+    /// Create an effect that ticks every `interval` seconds for `duration` seconds
+    ///
+    /// The interval is clamped to at least [MIN_EFFECT_INTERVAL] and negative durations to zero.
+    pub fn new(name: &str, kind: EffectKind, amount: T, interval: f32, duration: f32) -> Self {
+        HealthEffect {
+            name: name.to_owned(),
+            kind,
+            amount,
+            stacking: Stacking::Stack { max: usize::MAX },
+            source: None,
+            tick: Timer::from_seconds(interval.max(MIN_EFFECT_INTERVAL), true),
+            duration: Timer::from_seconds(duration.max(0.), false),
+        }
+    }
+
+    pub fn with_stacking(mut self, stacking: Stacking) -> Self {
+        self.stacking = stacking;
+        self
+    }
+
+    pub fn with_source(mut self, source: Entity) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Query if the effect has run for its full duration
+    pub fn is_finished(&self) -> bool {
+        self.duration.finished()
+    }
+
+    /// Get the remaining duration in seconds
+    pub fn remaining(&self) -> f32 {
+        self.duration.duration().as_secs_f32() - self.duration.elapsed_secs()
+    }
+}
+
+/// Effects currently active on an entity
+#[derive(Component)]
+pub struct HealthEffects<T: HealthValue = u32> {
+    effects: Vec<HealthEffect<T>>,
+}
+
+impl<T: HealthValue> Default for HealthEffects<T> {
+    fn default() -> Self {
+        HealthEffects { effects: vec![] }
+    }
+}
+
+impl<T: HealthValue> HealthEffects<T> {
+    /// Add an effect, following its stacking rule
+    pub fn add(&mut self, effect: HealthEffect<T>) {
+        let mut same = self
+            .effects
+            .iter_mut()
+            .filter(|e| e.name == effect.name)
+            .peekable();
+
+        match effect.stacking {
+            Stacking::Stack { max } => {
+                let count = same.count();
+                if count >= max {
+                    let mut to_remove = count + 1 - max;
+                    self.effects.retain(|e| {
+                        let remove = to_remove > 0 && e.name == effect.name;
+                        if remove {
+                            to_remove -= 1;
+                        }
+                        !remove
+                    });
+                }
+                if max > 0 {
+                    self.effects.push(effect);
+                }
+            },
+            Stacking::Refresh if same.peek().is_some() => {
+                for existing in same {
+                    existing.duration.reset();
+                }
+            },
+            Stacking::Intensify if same.peek().is_some() => {
+                for existing in same {
+                    existing.amount = existing.amount.saturating_add(effect.amount);
+                    existing.duration.reset();
+                }
+            },
+            Stacking::Replace => {
+                self.effects.retain(|e| e.name != effect.name);
+                self.effects.push(effect);
+            },
+            Stacking::Refresh | Stacking::Intensify => self.effects.push(effect),
+        }
+    }
+
+    /// Remove all effects with a name
+    pub fn remove(&mut self, name: &str) {
+        self.effects.retain(|e| e.name != name);
+    }
+
+    /// Remove all effects
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+
+    /// Iterate over the active effects
+    pub fn iter(&self) -> impl Iterator<Item = &HealthEffect<T>> {
+        self.effects.iter()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.effects.iter().any(|e| e.name == name)
+    }
+}
+
+/// Event to apply an effect to an entity
+pub struct ApplyEffect<T: HealthValue = u32> {
+    pub target: Entity,
+    pub effect: HealthEffect<T>,
+}
+
+pub(super) fn apply_effect_system<T: HealthValue>(
+    mut cmd: Commands,
+    entities: &Entities,
+    mut query: Query<&mut HealthEffects<T>>,
+    mut apply_reader: EventReader<ApplyEffect<T>>,
+) {
+    // effects for entities that do not have a HealthEffects component yet
+    let mut new_effects: HashMap<Entity, HealthEffects<T>> = HashMap::new();
+
+    for event in apply_reader.iter() {
+        match query.get_mut(event.target) {
+            Ok(mut effects) => effects.add(event.effect.clone()),
+            Err(_) => new_effects
+                .entry(event.target)
+                .or_default()
+                .add(event.effect.clone()),
+        }
+    }
+
+    for (entity, effects) in new_effects {
+        if entities.contains(entity) {
+            cmd.entity(entity).insert(effects);
+        }
+    }
+}
+
+pub(super) fn effect_tick_system<T: HealthValue>(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut HealthEffects<T>)>,
+    mut damage_writer: EventWriter<DamageEvent<T>>,
+    mut heal_writer: EventWriter<HealEvent<T>>,
+) {
+    for (entity, mut effects) in query.iter_mut() {
+        for effect in effects.effects.iter_mut() {
+            // don't tick past the end of the effect
+            let remaining = effect.duration.duration() - effect.duration.elapsed();
+            let delta = time.delta().min(remaining);
+            effect.duration.tick(delta);
+
+            // several ticks can finish in a long frame, each still gets its own event
+            let ticks = effect.tick.tick(delta).times_finished_this_tick();
+            if effect.amount.is_zero() {
+                continue;
+            }
+            for _ in 0..ticks {
+                match effect.kind {
+                    EffectKind::Damage(damage_type) => damage_writer.send(DamageEvent {
+                        target: entity,
+                        amount: effect.amount,
+                        source: effect.source,
+                        damage_type,
+                    }),
+                    EffectKind::Heal => heal_writer.send(HealEvent {
+                        target: entity,
+                        amount: effect.amount,
+                        source: effect.source,
+                    }),
+                }
+            }
+        }
+        effects.effects.retain(|e| !e.is_finished());
+    }
+}
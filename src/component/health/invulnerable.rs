@@ -0,0 +1,90 @@
+//! Temporary immunity to damage
+//!
+//! Entities with an [Invulnerable] component ignore all damage events until it runs out. Adding
+//! [HitInvulnerability] gives an entity a short invulnerability window every time it is hit.
+
+use bevy::prelude::*;
+
+use super::{damage::DamageType, HealthValue};
+
+/// Ignore all damage until the duration has passed, after which the component is removed
+#[derive(Component)]
+pub struct Invulnerable {
+    timer: Timer,
+}
+
+impl Invulnerable {
+    /// Become invulnerable for a duration in seconds
+    pub fn new(duration: f32) -> Self {
+        Invulnerable {
+            timer: Timer::from_seconds(duration, false),
+        }
+    }
+
+    /// Query if the invulnerability has run out
+    pub fn is_finished(&self) -> bool {
+        self.timer.finished()
+    }
+
+    /// Get the remaining duration in seconds
+    pub fn remaining(&self) -> f32 {
+        self.timer.duration().as_secs_f32() - self.timer.elapsed_secs()
+    }
+}
+
+/// Become [Invulnerable] for a duration in seconds after taking damage
+#[derive(Component)]
+pub struct HitInvulnerability(pub f32);
+
+/// Flash the entity's visibility while it is [Invulnerable]
+#[derive(Component)]
+pub struct InvulnerableFlash {
+    /// Time in seconds between toggling visibility
+    pub interval: f32,
+}
+
+/// Sent when damage was ignored because the target was invulnerable
+pub struct DamageBlocked<T: HealthValue = u32> {
+    pub entity: Entity,
+    pub source: Option<Entity>,
+    pub amount: T,
+    pub damage_type: DamageType,
+}
+
+pub(super) fn invulnerable_system(
+    mut cmd: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Invulnerable)>,
+) {
+    for (entity, mut invulnerable) in query.iter_mut() {
+        if invulnerable.timer.tick(time.delta()).finished() {
+            cmd.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
+
+pub(super) fn invulnerable_flash_system(
+    mut query: Query<(&Invulnerable, &InvulnerableFlash, &mut Visibility)>,
+) {
+    for (invulnerable, flash, mut visibility) in query.iter_mut() {
+        if flash.interval <= 0. {
+            continue;
+        }
+        let visible = (invulnerable.timer.elapsed_secs() / (2. * flash.interval)).fract() < 0.5;
+        if visibility.is_visible != visible {
+            visibility.is_visible = visible;
+        }
+    }
+}
+
+/// Make sure flashing entities are visible once they are no longer invulnerable
+pub(super) fn invulnerable_flash_end_system(
+    removed: RemovedComponents<Invulnerable>,
+    mut query: Query<&mut Visibility, With<InvulnerableFlash>>,
+) {
+    for entity in removed.iter() {
+        if let Ok(mut visibility) = query.get_mut(entity) {
+            visibility.is_visible = true;
+        }
+    }
+}
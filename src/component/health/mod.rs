@@ -0,0 +1,525 @@
+//! Keeps track of an amount of health
+//!
+//! Also supports a health cap and restoring to original health value.
+//!
+//! [HealthPlugin] can be added to deal damage and heal through events and to detect when entities
+//! die.
+
+pub mod damage;
+pub mod effect;
+pub mod invulnerable;
+pub mod regen;
+pub mod value;
+
+use std::{collections::HashSet, marker::PhantomData, ops::Deref};
+
+#[cfg(feature = "serde")]
+use bevy::reflect::ReflectDeserialize;
+use bevy::{ecs::system::SystemParam, prelude::*};
+#[cfg(feature = "egui")]
+use bevy_inspector_egui::Inspectable;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use self::value::HealthValue;
+use self::{
+    damage::{Armor, DamageBreakdown, DamageType, Resistances},
+    effect::{apply_effect_system, effect_tick_system, ApplyEffect},
+    invulnerable::*,
+    regen::{regen_damage_system, regen_system},
+};
+
+/// Health component, initialize only using then `new` function
+///
+/// Health is stored as a `u32` by default, any [HealthValue] such as `f32` can be used instead.
+#[cfg_attr(feature = "egui", derive(Inspectable))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+#[derive(Component, Reflect, Clone, Eq, Copy, PartialEq)]
+#[cfg_attr(not(feature = "serde"), reflect_value(Component))]
+#[cfg_attr(feature = "serde", reflect_value(Component, Deserialize))]
+pub struct Health<T: HealthValue = u32> {
+    original_hp: T,
+    current_hp: T,
+    health_cap: Option<T>,
+    /// Extra pool of health that is lost before current health
+    #[cfg_attr(feature = "serde", serde(default))]
+    shield: T,
+}
+
+/// Required by reflection, which overwrites the value right away when inserting a reflected
+/// [Health]
+impl<T: HealthValue> FromWorld for Health<T> {
+    fn from_world(_: &mut World) -> Self {
+        Health::new(T::zero())
+    }
+}
+
+/// How current health is adjusted when the max health changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaxHealthChange {
+    /// Keep the same percentage of health
+    Scale,
+    /// Keep the same health, lowering it if it is above the new max
+    Clamp,
+}
+
+impl<T: HealthValue> Health<T> {
+    /// Initialize health with a base value
+    pub fn new(base_hp: T) -> Self {
+        Health {
+            original_hp: base_hp,
+            current_hp: base_hp,
+            health_cap: None,
+            shield: T::zero(),
+        }
+    }
+
+    /// Set a health cap that health can never exceed
+    pub fn with_max(mut self, max_hp: T) -> Self {
+        self.health_cap = Some(max_hp);
+        self.current_hp = self.current_hp.min(max_hp);
+        self
+    }
+
+    /// Change the health cap
+    pub fn set_max(&mut self, max_hp: T, change: MaxHealthChange) {
+        self.current_hp = match change {
+            MaxHealthChange::Scale if self.max().is_zero() => self.current_hp.min(max_hp),
+            MaxHealthChange::Scale => self.current_hp.scale(max_hp, self.max()).min(max_hp),
+            MaxHealthChange::Clamp => self.current_hp.min(max_hp),
+        };
+        self.health_cap = Some(max_hp);
+    }
+
+    /// Lose health
+    ///
+    /// Any shield is lost first. Health cannot go lower then zero, and negative amounts are
+    /// ignored.
+    pub fn take(&mut self, amount: T) {
+        let absorbed = amount.min(self.shield);
+        self.shield = self.shield.saturating_sub(absorbed);
+        self.current_hp = self
+            .current_hp
+            .saturating_sub(amount.saturating_sub(absorbed));
+    }
+
+    /// Gain health
+    ///
+    /// Health will not exceed the max health, see [Health::max].
+    pub fn add(&mut self, amount: T) {
+        self.current_hp = self.current_hp.saturating_add(amount).min(self.max());
+    }
+
+    /// Gain health, with any health above the max health being added to the shield
+    pub fn add_overheal(&mut self, amount: T) {
+        let before = self.current_hp;
+        self.add(amount);
+        let healed = self.current_hp.saturating_sub(before);
+        self.add_shield(amount.saturating_sub(healed));
+    }
+
+    /// Gain shield
+    pub fn add_shield(&mut self, amount: T) {
+        self.shield = self.shield.saturating_add(amount);
+    }
+
+    /// Restore health to original value and remove any shield
+    pub fn reset(&mut self) {
+        self.current_hp = self.original_hp.min(self.max());
+        self.shield = T::zero();
+    }
+
+    /// Query if health is zero
+    pub fn is_zero(&self) -> bool {
+        self.current_hp.is_zero()
+    }
+
+    /// Get current health percentage based on max health
+    ///
+    /// Returns zero if max health is zero.
+    pub fn percent(&self) -> f32 {
+        let max = self.max();
+        if max.is_zero() {
+            0.
+        } else {
+            self.current_hp.to_f32() / max.to_f32()
+        }
+    }
+
+    /// Get the current health value
+    pub fn current(&self) -> T {
+        self.current_hp
+    }
+
+    /// Get the original health value
+    pub fn original(&self) -> T {
+        self.original_hp
+    }
+
+    /// Get the max health value
+    ///
+    /// This is the health cap if one was set, otherwise the original health value.
+    pub fn max(&self) -> T {
+        self.health_cap.unwrap_or(self.original_hp)
+    }
+
+    /// Get the health cap
+    pub fn cap(&self) -> Option<T> {
+        self.health_cap
+    }
+
+    /// Get the current shield value
+    pub fn shield(&self) -> T {
+        self.shield
+    }
+}
+
+impl<T: HealthValue> Deref for Health<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.current_hp
+    }
+}
+
+/// Event to deal damage to an entity
+pub struct DamageEvent<T: HealthValue = u32> {
+    pub target: Entity,
+    pub amount: T,
+    /// Entity responsible for the damage, if any
+    pub source: Option<Entity>,
+    pub damage_type: DamageType,
+}
+
+impl<T: HealthValue> DamageEvent<T> {
+    /// Physical damage without a source
+    pub fn new(target: Entity, amount: T) -> Self {
+        DamageEvent {
+            target,
+            amount,
+            source: None,
+            damage_type: DamageType::Physical,
+        }
+    }
+
+    pub fn with_source(mut self, source: Entity) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn with_type(mut self, damage_type: DamageType) -> Self {
+        self.damage_type = damage_type;
+        self
+    }
+}
+
+/// Event to heal an entity
+pub struct HealEvent<T: HealthValue = u32> {
+    pub target: Entity,
+    pub amount: T,
+    /// Entity responsible for the healing, if any
+    pub source: Option<Entity>,
+}
+
+/// Sent for every [DamageEvent] applied to an entity, describing how the damage was modified
+pub struct DamageTaken<T: HealthValue = u32> {
+    pub entity: Entity,
+    pub source: Option<Entity>,
+    pub breakdown: DamageBreakdown<T>,
+}
+
+/// Sent when health was changed by a [DamageEvent] or [HealEvent]
+pub struct HealthChanged<T: HealthValue = u32> {
+    pub entity: Entity,
+    pub old: T,
+    pub new: T,
+}
+
+/// Sent once when an entity's health reaches zero
+pub struct Died {
+    pub entity: Entity,
+    /// Source of the damage that killed the entity, if known
+    pub killer: Option<Entity>,
+}
+
+/// Marker added to entities whose health has reached zero
+///
+/// Dead entities ignore damage and healing events. Remove this marker and restore health to
+/// revive an entity.
+#[derive(Component)]
+pub struct Dead;
+
+/// Marker to despawn an entity and its children when it dies
+#[derive(Component)]
+pub struct DespawnOnDeath;
+
+/// Plugin to process damage and heal events and detect deaths
+///
+/// Use `HealthPlugin::new()` for the default `u32` health, or for example
+/// `HealthPlugin::<f32>::default()` for other health value types.
+pub struct HealthPlugin<T: HealthValue = u32> {
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl HealthPlugin {
+    pub fn new() -> Self {
+        HealthPlugin::default()
+    }
+}
+
+impl<T: HealthValue> Default for HealthPlugin<T> {
+    fn default() -> Self {
+        HealthPlugin {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: HealthValue> Plugin for HealthPlugin<T> {
+    fn build(&self, app: &mut App) {
+        // systems that do not depend on the health type are shared between health plugins
+        if !app.world.contains_resource::<Events<Died>>() {
+            app.add_event::<Died>()
+                .add_system(invulnerable_system)
+                .add_system(invulnerable_flash_system.after(invulnerable_system))
+                .add_system_to_stage(CoreStage::PostUpdate, despawn_on_death_system)
+                .add_system_to_stage(CoreStage::PostUpdate, invulnerable_flash_end_system);
+        }
+
+        app.add_event::<DamageEvent<T>>()
+            .add_event::<HealEvent<T>>()
+            .add_event::<DamageTaken<T>>()
+            .add_event::<HealthChanged<T>>()
+            .add_event::<DamageBlocked<T>>()
+            .add_event::<ApplyEffect<T>>()
+            .add_system(apply_effect_system::<T>)
+            .add_system(effect_tick_system::<T>.after(apply_effect_system::<T>))
+            .add_system(
+                damage_event_system::<T>
+                    .after(invulnerable_system)
+                    .after(effect_tick_system::<T>),
+            )
+            .add_system(regen_damage_system::<T>.after(damage_event_system::<T>))
+            .add_system(regen_system::<T>.after(regen_damage_system::<T>))
+            .add_system(heal_event_system::<T>.after(regen_system::<T>))
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                death_system::<T>.before(despawn_on_death_system),
+            );
+    }
+}
+
+#[derive(SystemParam)]
+struct DamageWriters<'w, 's, T: HealthValue> {
+    taken: EventWriter<'w, 's, DamageTaken<T>>,
+    blocked: EventWriter<'w, 's, DamageBlocked<T>>,
+    changed: EventWriter<'w, 's, HealthChanged<T>>,
+    died: EventWriter<'w, 's, Died>,
+}
+
+fn damage_event_system<T: HealthValue>(
+    mut cmd: Commands,
+    mut query: Query<&mut Health<T>, Without<Dead>>,
+    modifier_query: Query<(Option<&Resistances>, Option<&Armor<T>>)>,
+    invulnerable_query: Query<(Option<&Invulnerable>, Option<&HitInvulnerability>)>,
+    mut damage_reader: EventReader<DamageEvent<T>>,
+    mut writers: DamageWriters<T>,
+    // entities that became invulnerable this frame, before the component is inserted
+    mut hit_this_frame: Local<HashSet<Entity>>,
+) {
+    hit_this_frame.clear();
+
+    for event in damage_reader.iter() {
+        let mut health = match query.get_mut(event.target) {
+            Ok(health) => health,
+            Err(_) => continue,
+        };
+        // dead entities are filtered by the query, but may have died earlier this frame
+        if health.is_zero() {
+            continue;
+        }
+
+        let (invulnerable, hit_invulnerability) =
+            invulnerable_query.get(event.target).unwrap_or((None, None));
+        if invulnerable.is_some_and(|i| !i.is_finished()) || hit_this_frame.contains(&event.target)
+        {
+            writers.blocked.send(DamageBlocked {
+                entity: event.target,
+                source: event.source,
+                amount: event.amount,
+                damage_type: event.damage_type,
+            });
+            continue;
+        }
+
+        let (resistances, armor) = modifier_query.get(event.target).unwrap_or((None, None));
+        let mut breakdown =
+            DamageBreakdown::compute(event.amount, event.damage_type, resistances, armor);
+        let old = health.current();
+        let old_shield = health.shield();
+        health.take(breakdown.final_amount);
+        breakdown.shield_absorbed = old_shield.saturating_sub(health.shield());
+        breakdown.final_amount = breakdown
+            .final_amount
+            .saturating_sub(breakdown.shield_absorbed);
+
+        if let Some(HitInvulnerability(duration)) = hit_invulnerability {
+            if !breakdown.dealt().is_zero() {
+                cmd.entity(event.target)
+                    .insert(Invulnerable::new(*duration));
+                hit_this_frame.insert(event.target);
+            }
+        }
+
+        writers.taken.send(DamageTaken {
+            entity: event.target,
+            source: event.source,
+            breakdown,
+        });
+        if health.current() != old {
+            writers.changed.send(HealthChanged {
+                entity: event.target,
+                old,
+                new: health.current(),
+            });
+        }
+        if health.is_zero() {
+            cmd.entity(event.target).insert(Dead);
+            writers.died.send(Died {
+                entity: event.target,
+                killer: event.source,
+            });
+        }
+    }
+}
+
+fn heal_event_system<T: HealthValue>(
+    mut query: Query<&mut Health<T>, Without<Dead>>,
+    mut heal_reader: EventReader<HealEvent<T>>,
+    mut changed_writer: EventWriter<HealthChanged<T>>,
+) {
+    for event in heal_reader.iter() {
+        let mut health = match query.get_mut(event.target) {
+            Ok(health) => health,
+            Err(_) => continue,
+        };
+        if health.is_zero() {
+            continue;
+        }
+
+        let old = health.current();
+        health.add(event.amount);
+        if health.current() != old {
+            changed_writer.send(HealthChanged {
+                entity: event.target,
+                old,
+                new: health.current(),
+            });
+        }
+    }
+}
+
+/// Detect deaths caused by modifying [Health] directly
+fn death_system<T: HealthValue>(
+    mut cmd: Commands,
+    query: Query<(Entity, &Health<T>), Changed<Health<T>>>,
+    dead_query: Query<(), With<Dead>>,
+    mut died_writer: EventWriter<Died>,
+) {
+    for (entity, health) in query.iter() {
+        if health.is_zero() && !dead_query.contains(entity) {
+            cmd.entity(entity).insert(Dead);
+            died_writer.send(Died {
+                entity,
+                killer: None,
+            });
+        }
+    }
+}
+
+fn despawn_on_death_system(
+    mut cmd: Commands,
+    query: Query<(), With<DespawnOnDeath>>,
+    mut died_reader: EventReader<Died>,
+) {
+    for event in died_reader.iter() {
+        if query.contains(event.entity) {
+            cmd.entity(event.entity).despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run the same checks for any health value, using `v` to convert literals
+    fn check_amounts<T: HealthValue>(v: impl Fn(f32) -> T) {
+        let mut health = Health::new(v(10.));
+        health.take(v(4.));
+        assert_eq!(health.current(), v(6.));
+        health.take(v(20.));
+        assert_eq!(health.current(), v(0.));
+
+        health.add(v(3.));
+        assert_eq!(health.current(), v(3.));
+        health.add(v(20.));
+        assert_eq!(health.current(), v(10.));
+
+        health.add_shield(v(5.));
+        health.take(v(7.));
+        assert_eq!((health.current(), health.shield()), (v(8.), v(0.)));
+
+        health.add_overheal(v(6.));
+        assert_eq!((health.current(), health.shield()), (v(10.), v(4.)));
+    }
+
+    /// Negative amounts must not lower health or create a shield
+    fn check_negative_amounts<T: HealthValue>(v: impl Fn(f32) -> T) {
+        let mut health = Health::new(v(10.));
+        health.add(v(-15.));
+        assert_eq!(health.current(), v(10.));
+
+        health.take(v(-5.));
+        assert_eq!((health.current(), health.shield()), (v(10.), v(0.)));
+
+        health.add_shield(v(-5.));
+        assert_eq!(health.shield(), v(0.));
+
+        health.take(v(4.));
+        health.add_overheal(v(-5.));
+        assert_eq!((health.current(), health.shield()), (v(6.), v(0.)));
+    }
+
+    #[test]
+    fn amounts_u32() {
+        check_amounts(|x| x as u32);
+    }
+
+    #[test]
+    fn amounts_i32() {
+        check_amounts(|x| x as i32);
+        check_negative_amounts(|x| x as i32);
+    }
+
+    #[test]
+    fn amounts_f32() {
+        check_amounts(|x| x);
+        check_negative_amounts(|x| x);
+    }
+
+    #[test]
+    fn take_saturates_at_zero() {
+        let mut health = Health::new(5u32);
+        health.take(u32::MAX);
+        assert!(health.is_zero());
+
+        let mut health = Health::new(5i32);
+        health.take(i32::MAX);
+        assert_eq!(health.current(), 0);
+
+        let mut health = Health::new(5.);
+        health.take(f32::INFINITY);
+        assert_eq!(health.current(), 0.);
+    }
+}
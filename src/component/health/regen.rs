@@ -0,0 +1,86 @@
+//! Passive health regeneration
+
+use bevy::prelude::*;
+
+use super::{DamageTaken, HealEvent, Health, HealthValue};
+
+/// Regenerate health over time
+///
+/// Regeneration is paused for `delay` seconds after the entity takes damage and stops while health
+/// is full. Healing is sent as [HealEvent]s, so it goes through the same systems as any other
+/// healing.
+#[derive(Component)]
+pub struct Regeneration {
+    /// Health regenerated per second
+    pub per_second: f32,
+    /// Seconds to wait after taking damage before regenerating
+    pub delay: f32,
+    since_damage: f32,
+    accumulated: f32,
+}
+
+impl Regeneration {
+    pub fn new(per_second: f32) -> Self {
+        Regeneration {
+            per_second,
+            delay: 0.,
+            since_damage: 0.,
+            accumulated: 0.,
+        }
+    }
+
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        self.delay = delay;
+        self.since_damage = delay;
+        self
+    }
+
+    /// Query if regeneration is currently paused from taking damage
+    pub fn is_delayed(&self) -> bool {
+        self.since_damage < self.delay
+    }
+}
+
+pub(super) fn regen_damage_system<T: HealthValue>(
+    mut query: Query<&mut Regeneration, With<Health<T>>>,
+    mut taken_reader: EventReader<DamageTaken<T>>,
+) {
+    for event in taken_reader.iter() {
+        if event.breakdown.dealt().is_zero() {
+            continue;
+        }
+        if let Ok(mut regen) = query.get_mut(event.entity) {
+            regen.since_damage = 0.;
+            regen.accumulated = 0.;
+        }
+    }
+}
+
+pub(super) fn regen_system<T: HealthValue>(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Regeneration, &Health<T>)>,
+    mut heal_writer: EventWriter<HealEvent<T>>,
+) {
+    for (entity, mut regen, health) in query.iter_mut() {
+        regen.since_damage += time.delta_seconds();
+        if regen.is_delayed() {
+            continue;
+        }
+        if health.current() >= health.max() {
+            regen.accumulated = 0.;
+            continue;
+        }
+
+        regen.accumulated += regen.per_second * time.delta_seconds();
+        // integer health only heals once a whole point has accumulated
+        let amount = T::from_f32(regen.accumulated);
+        if !amount.is_zero() {
+            regen.accumulated -= amount.to_f32();
+            heal_writer.send(HealEvent {
+                target: entity,
+                amount,
+                source: None,
+            });
+        }
+    }
+}
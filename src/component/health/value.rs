@@ -0,0 +1,112 @@
+//! Numeric types that can be used as health values
+
+use std::fmt::Debug;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Numeric type used to store [Health](super::Health)
+///
+/// All operations saturate, so health never goes below zero or overflows. Negative amounts are
+/// treated as zero when adding or subtracting.
+pub trait HealthValue:
+    Copy + PartialOrd + Default + Debug + Send + Sync + 'static + Serialize + DeserializeOwned
+{
+    fn zero() -> Self {
+        Self::default()
+    }
+
+    /// Add, ignoring a negative `rhs`
+    fn saturating_add(self, rhs: Self) -> Self;
+
+    /// Subtract, stopping at zero and ignoring a negative `rhs`
+    fn saturating_sub(self, rhs: Self) -> Self;
+
+    /// Multiply by a factor, rounding to the nearest value for integer types
+    fn mul_f32(self, factor: f32) -> Self;
+
+    /// Multiply by `num / den` without losing precision, rounding down for integer types
+    ///
+    /// `den` must not be zero.
+    fn scale(self, num: Self, den: Self) -> Self;
+
+    /// Convert from a float, rounding down for integer types
+    fn from_f32(value: f32) -> Self;
+
+    fn to_f32(self) -> f32;
+
+    fn min(self, rhs: Self) -> Self {
+        if rhs < self {
+            rhs
+        } else {
+            self
+        }
+    }
+
+    fn is_zero(self) -> bool {
+        self <= Self::zero()
+    }
+}
+
+macro_rules! impl_int_health_value {
+    // `$wide` is large enough to hold the product of any two `$t`
+    ($($t:ty => $wide:ty),*) => {$(
+        impl HealthValue for $t {
+            fn saturating_add(self, rhs: Self) -> Self {
+                <$t>::saturating_add(self, rhs.max(0))
+            }
+
+            fn saturating_sub(self, rhs: Self) -> Self {
+                <$t>::saturating_sub(self, rhs.max(0)).max(0)
+            }
+
+            fn mul_f32(self, factor: f32) -> Self {
+                (self as f32 * factor).round() as $t
+            }
+
+            fn scale(self, num: Self, den: Self) -> Self {
+                Ord::min(self as $wide * num as $wide / den as $wide, <$t>::MAX as $wide) as $t
+            }
+
+            fn from_f32(value: f32) -> Self {
+                value as $t
+            }
+
+            fn to_f32(self) -> f32 {
+                self as f32
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_float_health_value {
+    ($($t:ty),*) => {$(
+        impl HealthValue for $t {
+            fn saturating_add(self, rhs: Self) -> Self {
+                self + rhs.max(0.)
+            }
+
+            fn saturating_sub(self, rhs: Self) -> Self {
+                (self - rhs.max(0.)).max(0.)
+            }
+
+            fn mul_f32(self, factor: f32) -> Self {
+                self * factor as $t
+            }
+
+            fn scale(self, num: Self, den: Self) -> Self {
+                self * num / den
+            }
+
+            fn from_f32(value: f32) -> Self {
+                value as $t
+            }
+
+            fn to_f32(self) -> f32 {
+                self as f32
+            }
+        }
+    )*};
+}
+
+impl_int_health_value!(u8 => u16, u16 => u32, u32 => u64, u64 => u128, i32 => i64, i64 => i128);
+impl_float_health_value!(f32, f64);
@@ -6,8 +6,12 @@
 use std::time::Duration;
 
 use bevy::prelude::*;
+#[cfg(feature = "serde")]
+use bevy::reflect::ReflectDeserialize;
 #[cfg(feature = "egui")]
 use bevy_inspector_egui::{egui::Ui, Context, Inspectable};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 use super::health::Health;
 use crate::misc::displacement::*;
@@ -29,7 +33,13 @@ pub struct DistanceLifetime {
 }
 
 /// Keeps track of the duration the entity was alive
-#[derive(Component)]
+///
+/// Serialized as the max duration in seconds.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "f32", into = "f32"))]
+#[derive(Component, Reflect, Default, Clone)]
+#[cfg_attr(not(feature = "serde"), reflect_value(Component))]
+#[cfg_attr(feature = "serde", reflect_value(Component, Deserialize))]
 pub struct DurationLifetime {
     timer: Timer,
 }
@@ -80,6 +90,18 @@ impl DurationLifetime {
     }
 }
 
+impl From<f32> for DurationLifetime {
+    fn from(max_duration: f32) -> Self {
+        DurationLifetime::new(max_duration)
+    }
+}
+
+impl From<DurationLifetime> for f32 {
+    fn from(lifetime: DurationLifetime) -> Self {
+        lifetime.timer.duration().as_secs_f32()
+    }
+}
+
 impl Lifetime for DurationLifetime {
     fn is_expired(&self) -> bool {
         self.timer.finished()
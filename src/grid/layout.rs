@@ -0,0 +1,195 @@
+//! Cell layouts for grids
+//!
+//! A [Grid](super::Grid) only stores cells in a rectangle indexed by `IVec2`. Layouts describe how
+//! those cells are arranged in the world, so the same occupancy API can be shared between square,
+//! hex and isometric maps.
+
+use bevy::prelude::*;
+
+/// Describes the shape and arrangement of grid cells
+pub trait GridLayout {
+    /// Positions of all cells adjacent to a position
+    fn neighbors(&self, pos: IVec2) -> Vec<IVec2>;
+
+    /// Number of steps needed to move between two positions
+    fn distance(&self, a: IVec2, b: IVec2) -> i32;
+
+    /// Convert a grid position to the world space center of the cell
+    fn pos_to_world(&self, pos: IVec2) -> Vec2;
+
+    /// Convert a world space position to the grid position of the cell containing it
+    fn world_to_pos(&self, world: Vec2) -> IVec2;
+
+    /// Z value to use for rendering a cell so that overlapping cells are drawn in order
+    fn depth(&self, _pos: IVec2) -> f32 {
+        0.
+    }
+}
+
+/// Square cells with four neighbors
+pub struct SquareLayout {
+    pub cell_size: f32,
+}
+
+impl GridLayout for SquareLayout {
+    fn neighbors(&self, pos: IVec2) -> Vec<IVec2> {
+        vec![
+            pos + IVec2::new(0, 1),
+            pos + IVec2::new(1, 0),
+            pos + IVec2::new(0, -1),
+            pos + IVec2::new(-1, 0),
+        ]
+    }
+
+    fn distance(&self, a: IVec2, b: IVec2) -> i32 {
+        let d = (a - b).abs();
+        d.x + d.y
+    }
+
+    fn pos_to_world(&self, pos: IVec2) -> Vec2 {
+        pos.as_vec2() * self.cell_size
+    }
+
+    fn world_to_pos(&self, world: Vec2) -> IVec2 {
+        (world / self.cell_size).round().as_ivec2()
+    }
+}
+
+/// Orientation of hexagon cells
+#[derive(Debug, Copy, Clone)]
+pub enum HexOrientation {
+    /// Rows of hexagons with a point facing up, odd rows are shifted right
+    PointyTop,
+    /// Columns of hexagons with a flat edge facing up, odd columns are shifted up
+    FlatTop,
+}
+
+/// Hexagon cells with six neighbors
+///
+/// Grid positions are stored in offset coordinates so that a rectangular map fits into the grid.
+/// Use [HexLayout::offset_to_axial] and [HexLayout::axial_to_offset] to convert to axial
+/// coordinates.
+pub struct HexLayout {
+    /// Distance from the center of a hexagon to a corner
+    pub size: f32,
+    pub orientation: HexOrientation,
+}
+
+const AXIAL_DIRS: [IVec2; 6] = [
+    IVec2::new(1, 0),
+    IVec2::new(1, -1),
+    IVec2::new(0, -1),
+    IVec2::new(-1, 0),
+    IVec2::new(-1, 1),
+    IVec2::new(0, 1),
+];
+
+impl HexLayout {
+    /// Convert offset coordinates to axial coordinates
+    pub fn offset_to_axial(&self, pos: IVec2) -> IVec2 {
+        match self.orientation {
+            HexOrientation::PointyTop => IVec2::new(pos.x - (pos.y - (pos.y & 1)) / 2, pos.y),
+            HexOrientation::FlatTop => IVec2::new(pos.x, pos.y - (pos.x - (pos.x & 1)) / 2),
+        }
+    }
+
+    /// Convert axial coordinates to offset coordinates
+    pub fn axial_to_offset(&self, axial: IVec2) -> IVec2 {
+        match self.orientation {
+            HexOrientation::PointyTop => {
+                IVec2::new(axial.x + (axial.y - (axial.y & 1)) / 2, axial.y)
+            },
+            HexOrientation::FlatTop => IVec2::new(axial.x, axial.y + (axial.x - (axial.x & 1)) / 2),
+        }
+    }
+
+    /// Round fractional axial coordinates to the nearest hexagon
+    fn axial_round(axial: Vec2) -> IVec2 {
+        let (q, r, s) = (axial.x, axial.y, -axial.x - axial.y);
+        let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+        IVec2::new(rq as i32, rr as i32)
+    }
+}
+
+impl GridLayout for HexLayout {
+    fn neighbors(&self, pos: IVec2) -> Vec<IVec2> {
+        let axial = self.offset_to_axial(pos);
+        AXIAL_DIRS
+            .iter()
+            .map(|dir| self.axial_to_offset(axial + *dir))
+            .collect()
+    }
+
+    fn distance(&self, a: IVec2, b: IVec2) -> i32 {
+        let d = self.offset_to_axial(a) - self.offset_to_axial(b);
+        (d.x.abs() + d.y.abs() + (d.x + d.y).abs()) / 2
+    }
+
+    fn pos_to_world(&self, pos: IVec2) -> Vec2 {
+        let axial = self.offset_to_axial(pos).as_vec2();
+        let sqrt3 = 3f32.sqrt();
+        let world = match self.orientation {
+            HexOrientation::PointyTop => {
+                Vec2::new(sqrt3 * axial.x + sqrt3 / 2. * axial.y, 1.5 * axial.y)
+            },
+            HexOrientation::FlatTop => {
+                Vec2::new(1.5 * axial.x, sqrt3 / 2. * axial.x + sqrt3 * axial.y)
+            },
+        };
+        world * self.size
+    }
+
+    fn world_to_pos(&self, world: Vec2) -> IVec2 {
+        let p = world / self.size;
+        let sqrt3 = 3f32.sqrt();
+        let axial = match self.orientation {
+            HexOrientation::PointyTop => Vec2::new(sqrt3 / 3. * p.x - p.y / 3., 2. / 3. * p.y),
+            HexOrientation::FlatTop => Vec2::new(2. / 3. * p.x, -p.x / 3. + sqrt3 / 3. * p.y),
+        };
+        self.axial_to_offset(HexLayout::axial_round(axial))
+    }
+}
+
+/// Diamond shaped isometric cells with four neighbors
+///
+/// Increasing x moves up and to the right on screen, increasing y moves up and to the left.
+pub struct IsoLayout {
+    /// Width and height of a single tile sprite
+    pub tile_size: Vec2,
+}
+
+impl GridLayout for IsoLayout {
+    fn neighbors(&self, pos: IVec2) -> Vec<IVec2> {
+        SquareLayout { cell_size: 1. }.neighbors(pos)
+    }
+
+    fn distance(&self, a: IVec2, b: IVec2) -> i32 {
+        SquareLayout { cell_size: 1. }.distance(a, b)
+    }
+
+    fn pos_to_world(&self, pos: IVec2) -> Vec2 {
+        let half = self.tile_size / 2.;
+        Vec2::new(
+            (pos.x - pos.y) as f32 * half.x,
+            (pos.x + pos.y) as f32 * half.y,
+        )
+    }
+
+    fn world_to_pos(&self, world: Vec2) -> IVec2 {
+        let half = self.tile_size / 2.;
+        let (a, b) = (world.x / half.x, world.y / half.y);
+        Vec2::new((a + b) / 2., (b - a) / 2.).round().as_ivec2()
+    }
+
+    /// Cells further up the screen are drawn behind cells lower down
+    fn depth(&self, pos: IVec2) -> f32 {
+        -(pos.x + pos.y) as f32
+    }
+}
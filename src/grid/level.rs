@@ -0,0 +1,208 @@
+//! Load grid levels from ASCII character maps or RON files
+//!
+//! Character maps are read top to bottom, so the first line of the map is the top row of the
+//! grid (highest y value). Each character is looked up in a legend that maps it to a prefab id.
+//! Spaces are always treated as empty cells.
+
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize};
+use thiserror::Error;
+
+use super::Grid;
+use crate::prefab::{inherit::list_positions, PrefabId, PrefabLib};
+
+/// Mapping from map characters to prefab ids
+pub type Legend = HashMap<char, PrefabId>;
+
+#[derive(Error, Debug)]
+pub enum LevelError {
+    #[error("failed to read level file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("unknown character '{ch}' at line {line}, column {column}")]
+    UnknownChar {
+        ch: char,
+        line: usize,
+        column: usize,
+    },
+    #[error("failed to parse level at line {line}, column {column}: {msg}")]
+    Parse {
+        msg: String,
+        line: usize,
+        column: usize,
+    },
+    #[error(
+        "spawn '{prefab_id}' at {pos} is outside of the {width}x{height} level{}",
+        location.map(|l| format!(" ({})", l)).unwrap_or_default()
+    )]
+    OutOfBounds {
+        prefab_id: PrefabId,
+        pos: IVec2,
+        width: i32,
+        height: i32,
+        /// Position of the spawn in the level source, if known
+        location: Option<Location>,
+    },
+    #[error(
+        "unknown prefab id '{prefab_id}' at {}",
+        location.map(|l| l.to_string()).unwrap_or_else(|| pos.to_string())
+    )]
+    UnknownPrefab {
+        prefab_id: PrefabId,
+        pos: IVec2,
+        /// Position of the spawn in the level source, if known
+        location: Option<Location>,
+    },
+}
+
+/// Line and column in the source of a level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// Request to spawn a prefab at a grid position
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SpawnRequest {
+    pub prefab_id: PrefabId,
+    pub pos: IVec2,
+    /// Where the spawn was defined in the level source, used for error messages
+    #[serde(skip)]
+    pub location: Option<Location>,
+}
+
+/// Grid dimensions and the prefabs to spawn in them
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Level {
+    pub width: i32,
+    pub height: i32,
+    #[serde(default)]
+    pub spawns: Vec<SpawnRequest>,
+}
+
+impl Level {
+    /// Parse a level from an ASCII character map
+    ///
+    /// The level width is the length of the longest line, shorter lines are padded with empty
+    /// cells.
+    pub fn from_char_map(map: &str, legend: &Legend) -> Result<Self, LevelError> {
+        let lines: Vec<&str> = map.lines().collect();
+        let height = lines.len() as i32;
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0) as i32;
+
+        let mut spawns = vec![];
+        for (row, line) in lines.iter().enumerate() {
+            for (col, ch) in line.chars().enumerate() {
+                if ch == ' ' {
+                    continue;
+                }
+                let prefab_id = legend.get(&ch).ok_or(LevelError::UnknownChar {
+                    ch,
+                    line: row + 1,
+                    column: col + 1,
+                })?;
+                spawns.push(SpawnRequest {
+                    prefab_id: prefab_id.clone(),
+                    pos: IVec2::new(col as i32, height - 1 - row as i32),
+                    location: Some(Location {
+                        line: row + 1,
+                        column: col + 1,
+                    }),
+                });
+            }
+        }
+
+        Ok(Level {
+            width,
+            height,
+            spawns,
+        })
+    }
+
+    /// Parse a level from a RON level description
+    pub fn from_ron(ron_string: &str) -> Result<Self, LevelError> {
+        let mut level: Level = ron::from_str(ron_string).map_err(|e| LevelError::Parse {
+            msg: e.code.to_string(),
+            line: e.position.line,
+            column: e.position.col,
+        })?;
+
+        if let Some(positions) = list_positions(ron_string, "spawns") {
+            for (spawn, (line, column)) in level.spawns.iter_mut().zip(positions) {
+                spawn.location = Some(Location { line, column });
+            }
+        }
+
+        for spawn in level.spawns.iter() {
+            if !level.in_bounds(&spawn.pos) {
+                return Err(LevelError::OutOfBounds {
+                    prefab_id: spawn.prefab_id.clone(),
+                    pos: spawn.pos,
+                    width: level.width,
+                    height: level.height,
+                    location: spawn.location,
+                });
+            }
+        }
+
+        Ok(level)
+    }
+
+    /// Load a character map level from a file
+    pub fn char_map_from_file(filepath: &str, legend: &Legend) -> Result<Self, LevelError> {
+        let map = fs::read_to_string(Path::new(&filepath))?;
+        Level::from_char_map(&map, legend)
+    }
+
+    /// Load a RON level from a file
+    pub fn ron_from_file(filepath: &str) -> Result<Self, LevelError> {
+        let ron_string = fs::read_to_string(Path::new(&filepath))?;
+        Level::from_ron(&ron_string)
+    }
+
+    /// Check that every spawn refers to a prefab that exists in the library
+    ///
+    /// Returns an error for every spawn with an unknown prefab.
+    pub fn check_prefabs<P: DeserializeOwned>(
+        &self,
+        lib: &PrefabLib<P>,
+    ) -> Result<(), Vec<LevelError>> {
+        let errors: Vec<LevelError> = self
+            .spawns
+            .iter()
+            .filter(|spawn| !lib.contains(&spawn.prefab_id))
+            .map(|spawn| LevelError::UnknownPrefab {
+                prefab_id: spawn.prefab_id.clone(),
+                pos: spawn.pos,
+                location: spawn.location,
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Construct an empty grid with the dimensions of the level
+    pub fn new_grid<T: PartialEq>(&self) -> Grid<T> {
+        Grid::new(self.width, self.height)
+    }
+
+    fn in_bounds(&self, pos: &IVec2) -> bool {
+        0 <= pos.x && pos.x < self.width && 0 <= pos.y && pos.y < self.height
+    }
+}
@@ -0,0 +1,224 @@
+//! Grid related utilities
+
+pub mod layout;
+#[cfg(feature = "prefab")]
+pub mod level;
+pub mod mover;
+
+use std::ops::{Index, IndexMut};
+
+use bevy::prelude::*;
+use thiserror::Error;
+
+use self::layout::GridLayout;
+
+#[derive(Error, Debug)]
+pub enum GridError {
+    #[error("tried to access position outside of grid {0}")]
+    OutOfBounds(IVec2),
+    #[error("expected {expected} cells for grid but got {found}")]
+    DimensionMismatch { expected: usize, found: usize },
+}
+
+/// Collection of grid positions that can be queried and manipulated
+///
+/// The grid is a read-only structure. It is not a source of truth. GridPositions are the actual
+/// source of truth. The grid is just a visual representation of where all the GridPosition objects
+/// are relative to each other.
+pub struct Grid<T: PartialEq> {
+    width: i32,
+    height: i32,
+    grid: Vec<Vec<T>>,
+}
+
+impl<T: PartialEq> Grid<T> {
+    /// Construct a new grid
+    pub fn new(width: i32, height: i32) -> Self {
+        // TODO this is sorta stupid lol (maybe just force T to derive Clone)
+        let grid_vec = (0..width * height).into_iter().map(|_| vec![]).collect();
+
+        Grid {
+            width,
+            height,
+            grid: grid_vec,
+        }
+    }
+
+    /// Construct a grid from existing cells
+    ///
+    /// Cells are laid out row by row, starting at the bottom left of the grid.
+    pub fn from_cells(width: i32, height: i32, cells: Vec<Vec<T>>) -> Result<Self, GridError> {
+        let expected = (width.max(0) * height.max(0)) as usize;
+        if cells.len() != expected {
+            return Err(GridError::DimensionMismatch {
+                expected,
+                found: cells.len(),
+            });
+        }
+
+        Ok(Grid {
+            width,
+            height,
+            grid: cells,
+        })
+    }
+
+    /// Check if position is within the grid
+    pub fn bounds_check(&self, pos: &IVec2) -> bool {
+        0 <= pos.x && pos.x < self.width && 0 <= pos.y && pos.y < self.height
+    }
+
+    pub fn pos_to_index(&self, pos: &IVec2) -> Result<usize, GridError> {
+        if self.bounds_check(pos) {
+            Ok((pos.y * self.width + pos.x) as usize)
+        } else {
+            Err(GridError::OutOfBounds(pos.to_owned()))
+        }
+    }
+
+    fn index_to_pos(&self, ind: usize) -> IVec2 {
+        IVec2::new(ind as i32 % self.width, ind as i32 / self.width)
+    }
+
+    /// Get reference to cell at position, or `None` if out of bounds
+    pub fn get(&self, pos: &IVec2) -> Option<&Vec<T>> {
+        self.get_cell(pos).ok()
+    }
+
+    /// Get mutable reference to cell at position, or `None` if out of bounds
+    pub fn get_mut(&mut self, pos: &IVec2) -> Option<&mut Vec<T>> {
+        self.get_cell_mut(pos).ok()
+    }
+
+    /// Get reference to cell at position
+    pub fn get_cell(&self, pos: &IVec2) -> Result<&Vec<T>, GridError> {
+        let ind = self.pos_to_index(pos)?;
+        // shouldn't panic (since already bounds checked)?
+        Ok(self.grid.get(ind).unwrap())
+    }
+
+    /// Get mutable reference to cell at position
+    pub fn get_cell_mut(&mut self, pos: &IVec2) -> Result<&mut Vec<T>, GridError> {
+        let ind = self.pos_to_index(pos)?;
+        Ok(self.grid.get_mut(ind).unwrap())
+    }
+
+    /// Insert a cell entity at position
+    pub fn insert_at(&mut self, pos: &IVec2, val: T) -> Result<(), GridError> {
+        self.get_cell_mut(pos)?.push(val);
+        Ok(())
+    }
+
+    /// Remove the first occurrence of a value from the cell at position
+    ///
+    /// Returns whether the value was found.
+    pub fn remove_at(&mut self, pos: &IVec2, val: &T) -> Result<bool, GridError> {
+        let cell = self.get_cell_mut(pos)?;
+        match cell.iter().position(|v| v == val) {
+            Some(ind) => {
+                cell.remove(ind);
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    /// Move a value from one cell to another
+    ///
+    /// Returns whether the value was found in the original cell. Nothing is moved if it wasn't.
+    pub fn move_value(&mut self, from: &IVec2, to: &IVec2, val: &T) -> Result<bool, GridError> {
+        let to_ind = self.pos_to_index(to)?;
+        let from_cell = self.get_cell_mut(from)?;
+        match from_cell.iter().position(|v| v == val) {
+            Some(ind) => {
+                let val = from_cell.remove(ind);
+                self.grid[to_ind].push(val);
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    /// Remove and return all values in the cell at position
+    pub fn take_cell(&mut self, pos: &IVec2) -> Result<Vec<T>, GridError> {
+        Ok(std::mem::take(self.get_cell_mut(pos)?))
+    }
+
+    /// Only keep the values for which the predicate returns true
+    pub fn retain<F: FnMut(&IVec2, &T) -> bool>(&mut self, mut f: F) {
+        let width = self.width;
+        for (ind, cell) in self.grid.iter_mut().enumerate() {
+            // can't borrow self for index_to_pos while iterating mutably
+            let pos = IVec2::new(ind as i32 % width, ind as i32 / width);
+            cell.retain(|v| f(&pos, v));
+        }
+    }
+
+    /// Find all positions that contain a given value
+    pub fn positions_of(&self, val: &T) -> Vec<IVec2> {
+        self.grid
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.contains(val))
+            .map(|(ind, _)| self.index_to_pos(ind))
+            .collect()
+    }
+
+    /// Query if cell contains a given entity
+    pub fn contains_at(&self, pos: &IVec2, val: T) -> Result<bool, GridError> {
+        Ok(self.get_cell(pos)?.contains(&val))
+    }
+
+    /// Query if a cell at position is empty
+    pub fn empty_at(&self, pos: &IVec2) -> Result<bool, GridError> {
+        Ok(self.get_cell(pos)?.is_empty())
+    }
+
+    /// Get the in bounds neighbors of a position for a given layout
+    pub fn neighbors<L: GridLayout>(&self, layout: &L, pos: &IVec2) -> Vec<IVec2> {
+        layout
+            .neighbors(*pos)
+            .into_iter()
+            .filter(|p| self.bounds_check(p))
+            .collect()
+    }
+
+    /// Wipe the entire map
+    pub fn clear(&mut self) {
+        for cell in self.grid.iter_mut() {
+            cell.clear();
+        }
+    }
+
+    /// Get grid width
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// Get grid height
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+}
+
+impl<T: PartialEq> Index<IVec2> for Grid<T> {
+    type Output = Vec<T>;
+
+    /// Panics if the position is out of bounds
+    fn index(&self, pos: IVec2) -> &Self::Output {
+        match self.get_cell(&pos) {
+            Ok(cell) => cell,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+impl<T: PartialEq> IndexMut<IVec2> for Grid<T> {
+    /// Panics if the position is out of bounds
+    fn index_mut(&mut self, pos: IVec2) -> &mut Self::Output {
+        match self.get_cell_mut(&pos) {
+            Ok(cell) => cell,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
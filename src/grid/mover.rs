@@ -0,0 +1,199 @@
+//! Turn based movement on a grid
+//!
+//! Entities with a [GridMover] are tracked in a `Grid<Entity>` resource. Requesting a move will
+//! validate the destination, update the logical position and then tween the entity's transform
+//! over to the new cell.
+
+use bevy::prelude::*;
+
+use super::Grid;
+use crate::misc::dir::Dir;
+
+/// Marker for entities that other movers cannot move into
+#[derive(Component)]
+pub struct Blocking;
+
+/// Component that moves an entity between grid cells
+#[derive(Component)]
+pub struct GridMover {
+    pos: IVec2,
+    cell_size: f32,
+    duration: f32,
+    pending: Option<Dir>,
+    tween: Option<Tween>,
+}
+
+struct Tween {
+    start: Vec2,
+    end: Vec2,
+    timer: Timer,
+}
+
+/// Sent when a move has been validated and the entity starts moving
+pub struct MoveStarted {
+    pub entity: Entity,
+    pub from: IVec2,
+    pub to: IVec2,
+}
+
+/// Sent when the entity has arrived at its destination
+pub struct MoveFinished {
+    pub entity: Entity,
+    pub pos: IVec2,
+}
+
+/// Sent when a move was rejected
+pub struct MoveBlocked {
+    pub entity: Entity,
+    pub pos: IVec2,
+    pub dir: Dir,
+}
+
+impl GridMover {
+    /// Create a mover at a position
+    ///
+    /// `cell_size` is the world size of a single cell and `duration` is how long a single move
+    /// takes in seconds.
+    pub fn new(pos: IVec2, cell_size: f32, duration: f32) -> Self {
+        GridMover {
+            pos,
+            cell_size,
+            duration,
+            pending: None,
+            tween: None,
+        }
+    }
+
+    /// Request a move in a direction
+    ///
+    /// The move will be processed on the next update. Requests made while already moving are
+    /// ignored.
+    pub fn request_move(&mut self, dir: Dir) {
+        if !self.is_moving() {
+            self.pending = Some(dir);
+        }
+    }
+
+    /// Query if the mover is currently tweening between cells
+    pub fn is_moving(&self) -> bool {
+        self.tween.is_some()
+    }
+
+    /// Get the logical grid position
+    pub fn pos(&self) -> IVec2 {
+        self.pos
+    }
+
+    /// Convert a grid position to world space
+    pub fn to_world(&self, pos: IVec2) -> Vec2 {
+        pos.as_vec2() * self.cell_size
+    }
+}
+
+/// Plugin to process grid movement
+///
+/// Requires a `Grid<Entity>` resource to be inserted.
+pub struct GridMoverPlugin;
+
+impl Plugin for GridMoverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MoveStarted>()
+            .add_event::<MoveFinished>()
+            .add_event::<MoveBlocked>()
+            .add_system(register_mover_system)
+            .add_system_to_stage(CoreStage::PostUpdate, remove_mover_system)
+            .add_system(move_request_system.after(register_mover_system))
+            .add_system(tween_system.after(move_request_system));
+    }
+}
+
+fn register_mover_system(
+    mut grid: ResMut<Grid<Entity>>,
+    query: Query<(Entity, &GridMover), Added<GridMover>>,
+) {
+    for (entity, mover) in query.iter() {
+        if let Err(err) = grid.insert_at(&mover.pos, entity) {
+            warn!("could not register grid mover {:?}: {}", entity, err);
+        }
+    }
+}
+
+/// Remove despawned movers and removed [GridMover] components from the grid
+fn remove_mover_system(mut grid: ResMut<Grid<Entity>>, removed: RemovedComponents<GridMover>) {
+    for entity in removed.iter() {
+        grid.retain(|_, e| *e != entity);
+    }
+}
+
+fn move_request_system(
+    mut grid: ResMut<Grid<Entity>>,
+    blocking_query: Query<(), With<Blocking>>,
+    mut query: Query<(Entity, &mut GridMover, &Transform)>,
+    mut started_writer: EventWriter<MoveStarted>,
+    mut blocked_writer: EventWriter<MoveBlocked>,
+) {
+    for (entity, mut mover, transform) in query.iter_mut() {
+        let dir = match mover.pending.take() {
+            Some(dir) => dir,
+            None => continue,
+        };
+
+        let from = mover.pos;
+        let to = from + IVec2::from(dir);
+
+        let blocked = match grid.get(&to) {
+            Some(cell) => to == from || cell.iter().any(|e| blocking_query.contains(*e)),
+            None => true,
+        };
+        if blocked {
+            blocked_writer.send(MoveBlocked {
+                entity,
+                pos: from,
+                dir,
+            });
+            continue;
+        }
+
+        if !grid.move_value(&from, &to, &entity).unwrap_or(false) {
+            let _ = grid.insert_at(&to, entity);
+        }
+
+        mover.pos = to;
+        mover.tween = Some(Tween {
+            start: transform.translation.truncate(),
+            end: mover.to_world(to),
+            timer: Timer::from_seconds(mover.duration, false),
+        });
+
+        started_writer.send(MoveStarted { entity, from, to });
+    }
+}
+
+fn tween_system(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut GridMover, &mut Transform)>,
+    mut writer: EventWriter<MoveFinished>,
+) {
+    for (entity, mut mover, mut transform) in query.iter_mut() {
+        let pos = mover.pos;
+        let tween = match mover.tween.as_mut() {
+            Some(tween) => tween,
+            None => continue,
+        };
+
+        tween.timer.tick(time.delta());
+        // zero length timers would otherwise produce NaN
+        let t = if tween.timer.finished() {
+            1.
+        } else {
+            tween.timer.percent()
+        };
+        let z = transform.translation.z;
+        transform.translation = tween.start.lerp(tween.end, t).extend(z);
+
+        if tween.timer.finished() {
+            mover.tween = None;
+            writer.send(MoveFinished { entity, pos });
+        }
+    }
+}
@@ -0,0 +1,494 @@
+//! Ingame health bar UI element
+//!
+//! Health bars can be kept in sync with an entity's [Health] by adding a [TrackHealth] component.
+//! Adding a [HealthBarStyle](style::HealthBarStyle) enables animations, gradients, borders and
+//! more. Health bars made of Bevy UI nodes, for HUDs or bars following an entity on screen, are
+//! in [ui].
+//!
+//! Health bars are [ProgressBar]s, which can also show mana, stamina or any other component with
+//! the help of [progress].
+
+pub mod progress;
+pub mod style;
+pub mod ui;
+
+use std::marker::PhantomData;
+
+use bevy::{ecs::entity::Entities, prelude::*, sprite::Anchor, ui::UiSystem};
+
+use self::{style::*, ui::*};
+use crate::component::health::{Dead, Health, HealthValue};
+
+/// Used to initalize a health bar
+pub struct HealthBarPrefab {
+    /// Physical dimensions of the health bar
+    pub dimension: Vec2,
+    /// Color of the health bar background
+    pub bg_color: Color,
+    /// Color of the health bar foreground
+    pub fg_color: Color,
+    /// Initial location of the health bar
+    pub translation: Vec3,
+}
+
+/// Bar that shows a percentage
+#[derive(Component)]
+pub struct ProgressBar {
+    percent: f32,
+    dimension: Vec2,
+    max_value: Option<f32>,
+    orientation: BarOrientation,
+}
+
+/// Progress bar showing health
+pub type HealthBar = ProgressBar;
+
+/// Direction a progress bar fills in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BarOrientation {
+    /// Fill from left to right
+    #[default]
+    Horizontal,
+    /// Fill from bottom to top
+    Vertical,
+    /// Ring made of `segments` sprites that fills clockwise from the top
+    ///
+    /// The width of the bar is the diameter of the ring and the height is its thickness. UI bars
+    /// are drawn horizontally instead.
+    Radial { segments: u32 },
+}
+
+impl BarOrientation {
+    /// Axis a linear bar fills along
+    fn axis(&self) -> Vec2 {
+        match self {
+            BarOrientation::Vertical => Vec2::Y,
+            _ => Vec2::X,
+        }
+    }
+
+    fn anchor(&self) -> Anchor {
+        match self {
+            BarOrientation::Vertical => Anchor::BottomCenter,
+            _ => Anchor::CenterLeft,
+        }
+    }
+
+    /// Size of a linear bar of `dimension` filled up to `percent`
+    fn fill(&self, dimension: Vec2, percent: f32) -> Vec2 {
+        let axis = self.axis();
+        dimension * (Vec2::ONE - axis + axis * percent)
+    }
+}
+
+impl ProgressBar {
+    pub fn set_percent(&mut self, percent: f32) {
+        self.percent = percent.clamp(0.0, 1.0);
+    }
+    pub fn add_percent(&mut self, percent: f32) {
+        self.percent = (self.percent + percent).clamp(0.0, 1.0);
+    }
+    pub fn percent(&self) -> f32 {
+        self.percent
+    }
+
+    /// Set the value of a full bar, used to place segment ticks
+    ///
+    /// This is set automatically for bars that [TrackHealth].
+    pub fn set_max_value(&mut self, max_value: f32) {
+        self.max_value = Some(max_value);
+    }
+    pub fn max_value(&self) -> Option<f32> {
+        self.max_value
+    }
+    pub fn orientation(&self) -> BarOrientation {
+        self.orientation
+    }
+}
+
+impl ProgressBar {
+    pub fn new(dimension: Vec2) -> Self {
+        ProgressBar {
+            percent: 1.,
+            dimension,
+            max_value: None,
+            orientation: BarOrientation::Horizontal,
+        }
+    }
+
+    pub fn with_orientation(mut self, orientation: BarOrientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+}
+
+/// Keep a [HealthBar] in sync with the [Health] of another entity
+#[derive(Component)]
+pub struct TrackHealth {
+    target: HealthBarTarget,
+    on_death: OnTargetDeath,
+    hidden: bool,
+}
+
+/// Entity whose health is shown by a health bar
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HealthBarTarget {
+    /// Parent of the health bar, for bars spawned as children
+    Parent,
+    Entity(Entity),
+}
+
+/// What happens to a health bar when its target dies or is despawned
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OnTargetDeath {
+    /// Hide the health bar until the target is alive again
+    Hide,
+    /// Despawn the health bar
+    Despawn,
+    /// Keep showing the empty health bar
+    Nothing,
+}
+
+impl TrackHealth {
+    /// Track the health of the health bar's parent
+    pub fn parent() -> Self {
+        TrackHealth::new(HealthBarTarget::Parent)
+    }
+
+    /// Track the health of another entity
+    pub fn entity(target: Entity) -> Self {
+        TrackHealth::new(HealthBarTarget::Entity(target))
+    }
+
+    pub fn new(target: HealthBarTarget) -> Self {
+        TrackHealth {
+            target,
+            on_death: OnTargetDeath::Hide,
+            hidden: false,
+        }
+    }
+
+    pub fn on_death(mut self, on_death: OnTargetDeath) -> Self {
+        self.on_death = on_death;
+        self
+    }
+
+    fn target_entity(&self, parent: Option<&Parent>) -> Option<Entity> {
+        match self.target {
+            HealthBarTarget::Parent => parent.map(|p| p.get()),
+            HealthBarTarget::Entity(target) => Some(target),
+        }
+    }
+
+    fn target_died(
+        &mut self,
+        cmd: &mut Commands,
+        health_bar: Entity,
+        progress_bar: &mut ProgressBar,
+        visibility: &mut Visibility,
+    ) {
+        progress_bar.set_percent(0.);
+        match self.on_death {
+            OnTargetDeath::Hide if !self.hidden => {
+                self.hidden = true;
+                visibility.is_visible = false;
+            },
+            OnTargetDeath::Despawn => cmd.entity(health_bar).despawn_recursive(),
+            _ => {},
+        }
+    }
+}
+
+#[derive(Component)]
+struct HealthBarForeground;
+
+#[derive(Component)]
+struct HealthBarBackground;
+
+/// Index of a segment of a radial bar
+#[derive(Component)]
+struct RadialSegment(u32);
+
+/// Color of a part of the health bar before fading is applied
+#[derive(Component)]
+struct BaseColor(Color);
+
+// small depth offsets so the parts of a health bar are always drawn in the same order
+const BORDER_Z: f32 = -0.001;
+const TRAIL_Z: f32 = 0.001;
+const FOREGROUND_Z: f32 = 0.002;
+const TICK_Z: f32 = 0.003;
+
+/// Plugin for health bars showing health of type `T`
+///
+/// Add one plugin per health type, such as `HealthBarPlugin::<f32>::default()` alongside
+/// `HealthBarPlugin::new()` for `u32` health.
+pub struct HealthBarPlugin<T: HealthValue = u32> {
+    phantom: PhantomData<T>,
+}
+
+impl HealthBarPlugin {
+    pub fn new() -> Self {
+        HealthBarPlugin {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: HealthValue> Default for HealthBarPlugin<T> {
+    fn default() -> Self {
+        HealthBarPlugin {
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// Marks that the systems shared between health bar plugins have been added
+struct HealthBarSystems;
+
+/// Systems that update the percentage of bars, which run before the bars are drawn
+#[derive(SystemLabel)]
+struct SyncBars;
+
+impl<T: HealthValue> Plugin for HealthBarPlugin<T> {
+    fn build(&self, app: &mut App) {
+        // systems that do not depend on the health type are shared between health bar plugins
+        if !app.world.contains_resource::<HealthBarSystems>() {
+            app.insert_resource(HealthBarSystems)
+                .add_system(missing_target_system.label(SyncBars))
+                .add_system(style_setup_system)
+                .add_system(
+                    health_bar_animation_system
+                        .after(SyncBars)
+                        .before(health_bar_system),
+                )
+                .add_system(health_bar_ticks_system.after(SyncBars))
+                .add_system(health_bar_system.after(SyncBars))
+                .add_system(health_bar_style_system.after(health_bar_system))
+                .add_system(ui_health_bar_system.after(health_bar_animation_system))
+                .add_system_to_stage(
+                    CoreStage::PostUpdate,
+                    world_anchor_system.before(UiSystem::Flex),
+                );
+        }
+
+        app.add_system(sync_health::<T>.label(SyncBars));
+    }
+}
+
+/// Spawn a new health bar from prefab
+pub fn spawn_health_bar(cmd: &mut Commands, prefab: HealthBarPrefab) -> Entity {
+    spawn_progress_bar(cmd, prefab, BarOrientation::Horizontal)
+}
+
+/// Spawn a new progress bar from prefab that fills in a direction
+pub fn spawn_progress_bar(
+    cmd: &mut Commands,
+    prefab: HealthBarPrefab,
+    orientation: BarOrientation,
+) -> Entity {
+    let parent = cmd.spawn().id();
+    cmd.entity(parent)
+        .insert(ProgressBar::new(prefab.dimension).with_orientation(orientation))
+        .insert_bundle(SpatialBundle::from_transform(Transform::from_translation(
+            prefab.translation,
+        )));
+
+    if let BarOrientation::Radial { segments } = orientation {
+        spawn_radial_segments(cmd, &prefab, parent, segments);
+        return parent;
+    }
+
+    let bg = cmd.spawn().id();
+    let fg = cmd.spawn().id();
+    cmd.entity(parent).push_children(&[fg, bg]);
+
+    let anchor = orientation.anchor();
+
+    cmd.entity(bg)
+        .insert_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: prefab.bg_color,
+                custom_size: Some(prefab.dimension),
+                anchor: anchor.clone(),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(HealthBarBackground)
+        .insert(BaseColor(prefab.bg_color));
+
+    cmd.entity(fg)
+        .insert_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: prefab.fg_color,
+                custom_size: Some(prefab.dimension),
+                anchor,
+                ..default()
+            },
+            transform: Transform::from_xyz(0., 0., FOREGROUND_Z),
+            ..default()
+        })
+        .insert(HealthBarForeground)
+        .insert(BaseColor(prefab.fg_color));
+
+    return parent;
+}
+
+/// Length and thickness of each segment of a radial bar
+fn radial_segment_size(dimension: Vec2, segments: u32) -> Vec2 {
+    let radius = (dimension.x - dimension.y) / 2.;
+    Vec2::new(
+        std::f32::consts::TAU * radius / segments as f32,
+        dimension.y,
+    )
+}
+
+fn spawn_radial_segments(
+    cmd: &mut Commands,
+    prefab: &HealthBarPrefab,
+    parent: Entity,
+    segments: u32,
+) {
+    let radius = (prefab.dimension.x - prefab.dimension.y) / 2.;
+    let size = radial_segment_size(prefab.dimension, segments);
+
+    for i in 0..segments {
+        // segments start on the ring and point clockwise, so they can be shortened to fill
+        let angle = std::f32::consts::TAU * i as f32 / segments as f32;
+        let start = Vec2::new(angle.sin(), angle.cos()) * radius;
+        let rotation = Quat::from_rotation_z(-angle);
+
+        for (color, z, foreground) in [
+            (prefab.bg_color, 0., false),
+            (prefab.fg_color, FOREGROUND_Z, true),
+        ] {
+            let segment = cmd
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(size),
+                        anchor: Anchor::CenterLeft,
+                        ..default()
+                    },
+                    transform: Transform::from_translation(start.extend(z)).with_rotation(rotation),
+                    ..default()
+                })
+                .insert(BaseColor(color))
+                .insert(RadialSegment(i))
+                .id();
+            if foreground {
+                cmd.entity(segment).insert(HealthBarForeground);
+            } else {
+                cmd.entity(segment).insert(HealthBarBackground);
+            }
+            cmd.entity(parent).add_child(segment);
+        }
+    }
+}
+
+/// Spawn a new health bar that tracks the health of a target entity
+///
+/// The health bar is added as a child of the target, so the prefab translation is relative to it.
+pub fn spawn_health_bar_for(cmd: &mut Commands, prefab: HealthBarPrefab, target: Entity) -> Entity {
+    let health_bar = spawn_health_bar(cmd, prefab);
+    cmd.entity(health_bar).insert(TrackHealth::parent());
+    cmd.entity(target).add_child(health_bar);
+    health_bar
+}
+
+type ForegroundFilter = (With<HealthBarForeground>, Without<HealthBarBackground>);
+// radial background segments keep their size
+type BackgroundFilter = (
+    With<HealthBarBackground>,
+    Without<HealthBarForeground>,
+    Without<RadialSegment>,
+);
+
+fn health_bar_system(
+    parent_query: Query<(&ProgressBar, Option<&BarAnimation>, &Children)>,
+    mut fg_query: Query<(&mut Sprite, Option<&RadialSegment>), ForegroundFilter>,
+    mut bg_query: Query<&mut Sprite, BackgroundFilter>,
+) {
+    for (progress_bar, animation, children) in parent_query.iter() {
+        let percent = animation.map_or(progress_bar.percent, |a| a.displayed);
+        let orientation = progress_bar.orientation;
+        for &child in children.iter() {
+            if let Ok((mut sprite, segment)) = fg_query.get_mut(child) {
+                let size = match (orientation, segment) {
+                    (BarOrientation::Radial { segments }, Some(segment)) => {
+                        let size = radial_segment_size(progress_bar.dimension, segments);
+                        let fill = (percent * segments as f32 - segment.0 as f32).clamp(0., 1.);
+                        Vec2::new(size.x * fill, size.y)
+                    },
+                    _ => orientation.fill(progress_bar.dimension, percent),
+                };
+                sprite.custom_size = Some(size);
+            }
+            if let Ok(mut sprite) = bg_query.get_mut(child) {
+                sprite.custom_size = Some(progress_bar.dimension);
+            }
+        }
+    }
+}
+
+/// Utility system that syncs bevy_bobs's health bar with health component
+///
+/// Added by [HealthBarPlugin]. Only bars whose target has a `Health<T>` are updated, bars whose
+/// target no longer exists are handled by the plugin separately.
+pub fn sync_health<T: HealthValue>(
+    mut cmd: Commands,
+    mut bar_query: Query<(
+        Entity,
+        &mut HealthBar,
+        &mut TrackHealth,
+        &mut Visibility,
+        Option<&Parent>,
+    )>,
+    health_query: Query<(&Health<T>, Option<&Dead>)>,
+) {
+    for (entity, mut health_bar, mut track, mut visibility, parent) in bar_query.iter_mut() {
+        let health = match track
+            .target_entity(parent)
+            .and_then(|target| health_query.get(target).ok())
+        {
+            Some(health) => health,
+            // the target uses a different health type or is handled by missing_target_system
+            None => continue,
+        };
+
+        match health {
+            (health, None) if !health.is_zero() => {
+                health_bar.set_percent(health.percent());
+                health_bar.max_value = Some(health.max().to_f32());
+                if track.hidden {
+                    track.hidden = false;
+                    visibility.is_visible = true;
+                }
+            },
+            _ => track.target_died(&mut cmd, entity, &mut health_bar, &mut visibility),
+        }
+    }
+}
+
+/// Treat bars whose target no longer exists as if the target died
+fn missing_target_system(
+    mut cmd: Commands,
+    entities: &Entities,
+    mut bar_query: Query<(
+        Entity,
+        &mut HealthBar,
+        &mut TrackHealth,
+        &mut Visibility,
+        Option<&Parent>,
+    )>,
+) {
+    for (entity, mut health_bar, mut track, mut visibility, parent) in bar_query.iter_mut() {
+        let exists = track
+            .target_entity(parent)
+            .is_some_and(|target| entities.contains(target));
+        if !exists {
+            track.target_died(&mut cmd, entity, &mut health_bar, &mut visibility);
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Progress bars bound to any component
+//!
+//! Bars can show anything that can be expressed as a percentage, such as mana, stamina or reload
+//! progress. Either implement [Progress] for the component or pass a closure to
+//! [TrackProgress::new].
+//!
+//! ```ignore
+//! app.add_plugin(ProgressPlugin::<Mana>::new());
+//!
+//! cmd.entity(bar).insert(TrackProgress::<Mana>::parent());
+//! // or bind a single field without implementing Progress
+//! cmd.entity(bar).insert(TrackProgress::new(HealthBarTarget::Parent, |s: &Stamina| s.0 / 100.));
+//! ```
+
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+use super::{HealthBarTarget, ProgressBar, SyncBars};
+use crate::component::health::{Health, HealthValue};
+
+/// Component that can be shown by a [ProgressBar]
+pub trait Progress: Component {
+    /// Percentage from `0.0` to `1.0`
+    fn progress(&self) -> f32;
+
+    /// Value of a full bar, used to place segment ticks
+    fn max_value(&self) -> Option<f32> {
+        None
+    }
+}
+
+impl<T: HealthValue> Progress for Health<T> {
+    fn progress(&self) -> f32 {
+        self.percent()
+    }
+
+    fn max_value(&self) -> Option<f32> {
+        Some(self.max().to_f32())
+    }
+}
+
+type ProgressFn<C, R> = Box<dyn Fn(&C) -> R + Send + Sync>;
+
+/// Keep a [ProgressBar] in sync with a component `C` on another entity
+#[derive(Component)]
+pub struct TrackProgress<C: Component> {
+    target: HealthBarTarget,
+    progress: ProgressFn<C, f32>,
+    max_value: ProgressFn<C, Option<f32>>,
+}
+
+impl<C: Component> TrackProgress<C> {
+    /// Track a component using a closure that returns a percentage from `0.0` to `1.0`
+    pub fn new(
+        target: HealthBarTarget,
+        progress: impl Fn(&C) -> f32 + Send + Sync + 'static,
+    ) -> Self {
+        TrackProgress {
+            target,
+            progress: Box::new(progress),
+            max_value: Box::new(|_| None),
+        }
+    }
+
+    /// Set the closure that returns the value of a full bar
+    pub fn with_max_value(mut self, max_value: impl Fn(&C) -> f32 + Send + Sync + 'static) -> Self {
+        self.max_value = Box::new(move |component| Some(max_value(component)));
+        self
+    }
+}
+
+impl<C: Progress> TrackProgress<C> {
+    /// Track the component on the bar's parent
+    pub fn parent() -> Self {
+        TrackProgress::from_trait(HealthBarTarget::Parent)
+    }
+
+    /// Track the component on another entity
+    pub fn entity(target: Entity) -> Self {
+        TrackProgress::from_trait(HealthBarTarget::Entity(target))
+    }
+
+    fn from_trait(target: HealthBarTarget) -> Self {
+        TrackProgress {
+            target,
+            progress: Box::new(C::progress),
+            max_value: Box::new(C::max_value),
+        }
+    }
+}
+
+/// Adds the system that syncs bars with [TrackProgress] for component `C`
+///
+/// The bars themselves are drawn by [HealthBarPlugin](super::HealthBarPlugin), which must be added
+/// as well.
+pub struct ProgressPlugin<C: Component> {
+    phantom: PhantomData<C>,
+}
+
+impl<C: Component> ProgressPlugin<C> {
+    pub fn new() -> Self {
+        ProgressPlugin {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<C: Component> Default for ProgressPlugin<C> {
+    fn default() -> Self {
+        ProgressPlugin::new()
+    }
+}
+
+impl<C: Component> Plugin for ProgressPlugin<C> {
+    fn build(&self, app: &mut App) {
+        app.add_system(sync_progress::<C>.label(SyncBars));
+    }
+}
+
+/// Sync bars with the component `C` of their target
+///
+/// Bars whose target doesn't have the component keep their last value.
+pub fn sync_progress<C: Component>(
+    mut bar_query: Query<(&mut ProgressBar, &TrackProgress<C>, Option<&Parent>)>,
+    target_query: Query<&C>,
+) {
+    for (mut progress_bar, track, parent) in bar_query.iter_mut() {
+        let target = match track.target {
+            HealthBarTarget::Parent => parent.map(|p| p.get()),
+            HealthBarTarget::Entity(target) => Some(target),
+        };
+        let component = match target.and_then(|target| target_query.get(target).ok()) {
+            Some(component) => component,
+            None => continue,
+        };
+
+        progress_bar.set_percent((track.progress)(component));
+        if let Some(max_value) = (track.max_value)(component) {
+            progress_bar.set_max_value(max_value);
+        }
+    }
+}
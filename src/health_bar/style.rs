@@ -0,0 +1,385 @@
+//! Animations and styling for health bars
+//!
+//! ```ignore
+//! cmd.entity(health_bar).insert(HealthBarStyle {
+//!     lerp_speed: Some(10.),
+//!     trail: Some(DamageTrail::new(Color::WHITE)),
+//!     gradient: Some(Gradient::traffic_light()),
+//!     fade: Some(Fade::new(2., 0.5)),
+//!     ..default()
+//! });
+//! ```
+
+use bevy::prelude::*;
+
+use super::{
+    ui::UiHealthBar, BarOrientation, BaseColor, HealthBar, HealthBarForeground, BORDER_Z, TICK_Z,
+    TRAIL_Z,
+};
+
+/// Optional styling for a [HealthBar], added to the health bar entity
+///
+/// The trail, border and ticks are only drawn for horizontal and vertical sprite health bars.
+#[derive(Component, Clone, Default)]
+pub struct HealthBarStyle {
+    /// How fast the bar moves towards the current health, instantly if `None`
+    pub lerp_speed: Option<f32>,
+    pub trail: Option<DamageTrail>,
+    /// Color the foreground based on the health percentage
+    pub gradient: Option<Gradient>,
+    pub border: Option<Border>,
+    pub ticks: Option<Ticks>,
+    /// Only show the health bar while damaged
+    pub fade: Option<Fade>,
+}
+
+impl HealthBarStyle {
+    /// Color of the foreground after applying the gradient
+    pub(super) fn foreground_color(&self, base: Color, percent: f32) -> Color {
+        self.gradient
+            .as_ref()
+            .map_or(base, |gradient| gradient.sample(percent))
+    }
+}
+
+/// Segment that shows recently lost health before shrinking down to the current health
+#[derive(Clone)]
+pub struct DamageTrail {
+    pub color: Color,
+    /// Seconds to wait after taking damage before shrinking
+    pub delay: f32,
+    /// Percentage of the bar the trail shrinks by per second
+    pub speed: f32,
+}
+
+impl DamageTrail {
+    pub fn new(color: Color) -> Self {
+        DamageTrail {
+            color,
+            delay: 0.5,
+            speed: 0.5,
+        }
+    }
+}
+
+/// Colors picked by health percentage, blending between stops
+#[derive(Clone)]
+pub struct Gradient {
+    stops: Vec<(f32, Color)>,
+}
+
+impl Gradient {
+    /// Create a gradient from `(percent, color)` stops
+    pub fn new(mut stops: Vec<(f32, Color)>) -> Self {
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Gradient { stops }
+    }
+
+    /// Red when empty, yellow at half and green when full
+    pub fn traffic_light() -> Self {
+        Gradient::new(vec![
+            (0., Color::RED),
+            (0.5, Color::YELLOW),
+            (1., Color::GREEN),
+        ])
+    }
+
+    /// Get the color at a percentage
+    ///
+    /// A NaN percentage is treated as zero.
+    pub fn sample(&self, percent: f32) -> Color {
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Color::WHITE,
+        };
+        if percent.is_nan() || percent <= first.0 {
+            return first.1;
+        }
+        if percent >= last.0 {
+            return last.1;
+        }
+
+        let i = match self.stops.iter().position(|(p, _)| *p > percent) {
+            Some(i) => i,
+            None => return last.1,
+        };
+        let (p0, c0) = self.stops[i - 1];
+        let (p1, c1) = self.stops[i];
+        let t = (percent - p0) / (p1 - p0);
+        let c0 = Vec4::from(c0.as_rgba_f32());
+        let c1 = Vec4::from(c1.as_rgba_f32());
+        let c = c0.lerp(c1, t);
+        Color::rgba(c.x, c.y, c.z, c.w)
+    }
+}
+
+/// Outline drawn around the health bar
+#[derive(Clone)]
+pub struct Border {
+    pub width: f32,
+    pub color: Color,
+}
+
+/// Lines drawn every `every` health
+///
+/// The health of a full bar is taken from [HealthBar::max_value].
+#[derive(Clone)]
+pub struct Ticks {
+    pub every: f32,
+    pub width: f32,
+    pub color: Color,
+}
+
+/// Fade out the health bar after it has been at full health for `delay` seconds
+#[derive(Clone)]
+pub struct Fade {
+    pub delay: f32,
+    /// Seconds it takes to fade out
+    pub duration: f32,
+}
+
+impl Fade {
+    pub fn new(delay: f32, duration: f32) -> Self {
+        Fade { delay, duration }
+    }
+
+    fn opacity(&self, since_full: f32) -> f32 {
+        if self.duration <= 0. {
+            return if since_full < self.delay { 1. } else { 0. };
+        }
+        1. - ((since_full - self.delay) / self.duration).clamp(0., 1.)
+    }
+}
+
+/// Animation state of a styled health bar
+#[derive(Component)]
+pub(super) struct BarAnimation {
+    /// Percentage currently shown by the foreground
+    pub(super) displayed: f32,
+    trail: f32,
+    trail_wait: f32,
+    last_percent: f32,
+    since_full: f32,
+    opacity: f32,
+    /// Max value the current ticks were placed for
+    tick_max: Option<f32>,
+}
+
+impl BarAnimation {
+    /// Apply the current fade opacity to a color
+    pub(super) fn fade(&self, mut color: Color) -> Color {
+        color.set_a(color.a() * self.opacity);
+        color
+    }
+}
+
+#[derive(Component)]
+pub(super) struct HealthBarTrail;
+
+#[derive(Component)]
+pub(super) struct HealthBarTick;
+
+pub(super) fn style_setup_system(
+    mut cmd: Commands,
+    query: Query<
+        (Entity, &HealthBar, &HealthBarStyle, Option<&UiHealthBar>),
+        Added<HealthBarStyle>,
+    >,
+) {
+    for (entity, health_bar, style, ui) in query.iter() {
+        // bars that start at full health start hidden when fading
+        let since_full = style
+            .fade
+            .as_ref()
+            .map_or(0., |fade| fade.delay + fade.duration);
+        let opacity = style
+            .fade
+            .as_ref()
+            .map_or(1., |fade| fade.opacity(since_full));
+        cmd.entity(entity).insert(BarAnimation {
+            displayed: health_bar.percent,
+            trail: health_bar.percent,
+            trail_wait: 0.,
+            last_percent: health_bar.percent,
+            since_full,
+            opacity,
+            tick_max: None,
+        });
+        let orientation = health_bar.orientation;
+        if ui.is_some() || matches!(orientation, BarOrientation::Radial { .. }) {
+            continue;
+        }
+
+        if let Some(trail) = &style.trail {
+            let trail = spawn_part(
+                &mut cmd,
+                trail.color,
+                orientation,
+                health_bar.dimension,
+                Vec3::new(0., 0., TRAIL_Z),
+            );
+            cmd.entity(trail).insert(HealthBarTrail);
+            cmd.entity(entity).add_child(trail);
+        }
+
+        if let Some(border) = &style.border {
+            let border = spawn_part(
+                &mut cmd,
+                border.color,
+                orientation,
+                health_bar.dimension + Vec2::splat(border.width * 2.),
+                (-orientation.axis() * border.width).extend(BORDER_Z),
+            );
+            cmd.entity(entity).add_child(border);
+        }
+    }
+}
+
+pub(super) fn health_bar_animation_system(
+    time: Res<Time>,
+    mut query: Query<(&HealthBar, &HealthBarStyle, &mut BarAnimation)>,
+) {
+    let dt = time.delta_seconds();
+    for (health_bar, style, mut animation) in query.iter_mut() {
+        let percent = health_bar.percent;
+        let damaged = percent < animation.last_percent;
+        animation.last_percent = percent;
+
+        animation.displayed = match style.lerp_speed {
+            Some(speed) if (percent - animation.displayed).abs() > 0.001 => {
+                let t = 1. - (-speed * dt).exp();
+                animation.displayed + (percent - animation.displayed) * t
+            },
+            _ => percent,
+        };
+
+        if let Some(trail) = &style.trail {
+            if damaged {
+                animation.trail_wait = trail.delay;
+            }
+            if percent >= animation.trail {
+                animation.trail = percent;
+            } else if animation.trail_wait > 0. {
+                animation.trail_wait -= dt;
+            } else {
+                animation.trail = (animation.trail - trail.speed * dt).max(animation.displayed);
+            }
+        }
+
+        if let Some(fade) = &style.fade {
+            if percent < 1. {
+                animation.since_full = 0.;
+            } else {
+                animation.since_full += dt;
+            }
+            animation.opacity = fade.opacity(animation.since_full);
+        }
+    }
+}
+
+pub(super) fn health_bar_ticks_system(
+    mut cmd: Commands,
+    mut query: Query<(
+        Entity,
+        &HealthBar,
+        &HealthBarStyle,
+        &mut BarAnimation,
+        Option<&Children>,
+    )>,
+    ui_query: Query<(), With<UiHealthBar>>,
+    tick_query: Query<(), With<HealthBarTick>>,
+) {
+    for (entity, health_bar, style, mut animation, children) in query.iter_mut() {
+        let orientation = health_bar.orientation;
+        if ui_query.contains(entity) || matches!(orientation, BarOrientation::Radial { .. }) {
+            continue;
+        }
+        let (ticks, max_value) = match (&style.ticks, health_bar.max_value) {
+            (Some(ticks), Some(max_value)) => (ticks, max_value),
+            _ => continue,
+        };
+        if animation.tick_max == Some(max_value) {
+            continue;
+        }
+        animation.tick_max = Some(max_value);
+
+        for &child in children.into_iter().flatten() {
+            if tick_query.contains(child) {
+                cmd.entity(child).despawn_recursive();
+            }
+        }
+        if ticks.every <= 0. {
+            continue;
+        }
+
+        let axis = orientation.axis();
+        let length = health_bar.dimension.dot(axis);
+        let count = (max_value / ticks.every).ceil() as u32;
+        for i in 1..count {
+            let offset = length * (i as f32 * ticks.every / max_value);
+            let tick = spawn_part(
+                &mut cmd,
+                ticks.color,
+                orientation,
+                health_bar.dimension * (Vec2::ONE - axis) + axis * ticks.width,
+                (axis * (offset - ticks.width / 2.)).extend(TICK_Z),
+            );
+            cmd.entity(tick).insert(HealthBarTick);
+            cmd.entity(entity).add_child(tick);
+        }
+    }
+}
+
+pub(super) fn health_bar_style_system(
+    bar_query: Query<(&HealthBar, &HealthBarStyle, &BarAnimation, &Children)>,
+    mut part_query: Query<(
+        &mut Sprite,
+        &BaseColor,
+        Option<&HealthBarForeground>,
+        Option<&HealthBarTrail>,
+    )>,
+) {
+    for (health_bar, style, animation, children) in bar_query.iter() {
+        for &child in children.iter() {
+            let (mut sprite, base_color, fg, trail) = match part_query.get_mut(child) {
+                Ok(part) => part,
+                Err(_) => continue,
+            };
+
+            let color = match fg {
+                Some(_) => style.foreground_color(base_color.0, health_bar.percent),
+                None => base_color.0,
+            };
+            sprite.color = animation.fade(color);
+
+            if trail.is_some() {
+                sprite.custom_size = Some(
+                    health_bar
+                        .orientation
+                        .fill(health_bar.dimension, animation.trail),
+                );
+            }
+        }
+    }
+}
+
+fn spawn_part(
+    cmd: &mut Commands,
+    color: Color,
+    orientation: BarOrientation,
+    size: Vec2,
+    translation: Vec3,
+) -> Entity {
+    cmd.spawn_bundle(SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size),
+            anchor: orientation.anchor(),
+            ..default()
+        },
+        transform: Transform::from_translation(translation),
+        ..default()
+    })
+    .insert(BaseColor(color))
+    .id()
+}
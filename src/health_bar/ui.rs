@@ -0,0 +1,247 @@
+//! Health bars built from Bevy UI nodes
+//!
+//! UI health bars are useful for HUDs and boss bars. They can also be anchored to an entity in
+//! the world with [WorldAnchor], in which case they follow the entity on screen.
+//!
+//! UI health bars use the same [HealthBar] component as sprite health bars, so they work with
+//! [TrackHealth](super::TrackHealth) and the lerp, gradient and fade options of
+//! [HealthBarStyle](super::style::HealthBarStyle).
+
+use bevy::prelude::*;
+
+use super::{
+    style::{BarAnimation, HealthBarStyle},
+    BarOrientation, BaseColor, HealthBar, ProgressBar, TrackHealth,
+};
+
+/// Used to initialize a UI health bar
+pub struct UiHealthBarPrefab {
+    /// Size of the health bar in pixels
+    pub size: Vec2,
+    /// Color of the health bar background
+    pub bg_color: Color,
+    /// Color of the health bar foreground
+    pub fg_color: Color,
+    /// Position of the health bar on screen, ignored for bars with a [WorldAnchor]
+    pub position: UiRect<Val>,
+    /// Text style of a "current / max" label, no label is shown if `None`
+    pub label: Option<TextStyle>,
+}
+
+/// Marker for health bars made of UI nodes
+#[derive(Component)]
+pub struct UiHealthBar;
+
+/// Position a UI node above an entity in the world
+#[derive(Component)]
+pub struct WorldAnchor {
+    pub target: Entity,
+    /// World space offset from the target's position
+    pub offset: Vec3,
+    /// Camera used to project the position, the first active camera if `None`
+    pub camera: Option<Entity>,
+}
+
+impl WorldAnchor {
+    pub fn new(target: Entity, offset: Vec3) -> Self {
+        WorldAnchor {
+            target,
+            offset,
+            camera: None,
+        }
+    }
+}
+
+#[derive(Component)]
+pub(super) struct UiHealthBarForeground;
+
+#[derive(Component)]
+pub(super) struct UiHealthBarLabel;
+
+/// Spawn a new UI health bar from prefab
+pub fn spawn_ui_health_bar(cmd: &mut Commands, prefab: UiHealthBarPrefab) -> Entity {
+    spawn_ui_progress_bar(cmd, prefab, BarOrientation::Horizontal)
+}
+
+/// Spawn a new UI progress bar from prefab that fills in a direction
+///
+/// Radial bars are drawn horizontally.
+pub fn spawn_ui_progress_bar(
+    cmd: &mut Commands,
+    prefab: UiHealthBarPrefab,
+    orientation: BarOrientation,
+) -> Entity {
+    let parent = cmd
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(prefab.size.x), Val::Px(prefab.size.y)),
+                position_type: PositionType::Absolute,
+                position: prefab.position,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: prefab.bg_color.into(),
+            ..default()
+        })
+        .insert(ProgressBar::new(prefab.size).with_orientation(orientation))
+        .insert(UiHealthBar)
+        .insert(BaseColor(prefab.bg_color))
+        .id();
+
+    let fg = cmd
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(0.),
+                    bottom: Val::Px(0.),
+                    ..default()
+                },
+                ..default()
+            },
+            color: prefab.fg_color.into(),
+            ..default()
+        })
+        .insert(UiHealthBarForeground)
+        .insert(BaseColor(prefab.fg_color))
+        .id();
+    cmd.entity(parent).add_child(fg);
+
+    if let Some(label) = prefab.label {
+        let color = label.color;
+        let label = cmd
+            .spawn_bundle(TextBundle::from_section("", label))
+            .insert(UiHealthBarLabel)
+            .insert(BaseColor(color))
+            .id();
+        cmd.entity(parent).add_child(label);
+    }
+
+    parent
+}
+
+/// Spawn a new UI health bar that follows an entity on screen and tracks its health
+pub fn spawn_world_ui_health_bar(
+    cmd: &mut Commands,
+    prefab: UiHealthBarPrefab,
+    target: Entity,
+    offset: Vec3,
+) -> Entity {
+    let health_bar = spawn_ui_health_bar(cmd, prefab);
+    cmd.entity(health_bar)
+        .insert(TrackHealth::entity(target))
+        .insert(WorldAnchor::new(target, offset));
+    health_bar
+}
+
+type StyledBar<'a> = Option<(&'a HealthBarStyle, &'a BarAnimation)>;
+
+pub(super) fn ui_health_bar_system(
+    mut bar_query: Query<
+        (&HealthBar, &mut UiColor, &BaseColor, StyledBar, &Children),
+        With<UiHealthBar>,
+    >,
+    mut fg_query: Query<
+        (&mut Style, &mut UiColor, &BaseColor, &UiHealthBarForeground),
+        Without<UiHealthBar>,
+    >,
+    mut label_query: Query<(&mut Text, &BaseColor), With<UiHealthBarLabel>>,
+) {
+    for (health_bar, mut bg_color, bg_base, styled, children) in bar_query.iter_mut() {
+        let fade = |color| styled.map_or(color, |(_, animation)| animation.fade(color));
+        bg_color.0 = fade(bg_base.0);
+
+        for &child in children.iter() {
+            if let Ok((mut style, mut color, base, _)) = fg_query.get_mut(child) {
+                let (percent, fg_color) = match styled {
+                    Some((style, animation)) => (
+                        animation.displayed,
+                        style.foreground_color(base.0, health_bar.percent),
+                    ),
+                    None => (health_bar.percent, base.0),
+                };
+                match health_bar.orientation {
+                    BarOrientation::Vertical => style.size.height = Val::Percent(percent * 100.),
+                    _ => style.size.width = Val::Percent(percent * 100.),
+                }
+                color.0 = fade(fg_color);
+            }
+
+            if let Ok((mut text, base)) = label_query.get_mut(child) {
+                let label = match health_bar.max_value {
+                    Some(max) => format!("{:.0} / {:.0}", health_bar.percent * max, max),
+                    None => format!("{:.0}%", health_bar.percent * 100.),
+                };
+                if text.sections[0].value != label {
+                    text.sections[0].value = label;
+                }
+                text.sections[0].style.color = fade(base.0);
+            }
+        }
+    }
+}
+
+/// Compute the global transform of an entity from its local transforms
+///
+/// Used instead of [GlobalTransform] because anchors are positioned before transforms are
+/// propagated for the current frame.
+fn world_transform(
+    entity: Entity,
+    query: &Query<(&Transform, Option<&Parent>)>,
+) -> Option<GlobalTransform> {
+    let (transform, mut parent) = query.get(entity).ok()?;
+    let mut global = GlobalTransform::from(*transform);
+    while let Some(entity) = parent {
+        let (transform, next) = query.get(entity.get()).ok()?;
+        global = GlobalTransform::from(*transform) * global;
+        parent = next;
+    }
+    Some(global)
+}
+
+/// Position anchored nodes before the UI layout of the current frame is computed
+///
+/// The node size used to center the bar comes from the previous layout.
+pub(super) fn world_anchor_system(
+    mut query: Query<(&WorldAnchor, &mut Style, Option<&Node>)>,
+    transform_query: Query<(&Transform, Option<&Parent>)>,
+    camera_query: Query<(Entity, &Camera)>,
+) {
+    for (anchor, mut style, node) in query.iter_mut() {
+        let camera = camera_query
+            .iter()
+            .find(|(entity, camera)| match anchor.camera {
+                Some(anchor_camera) => *entity == anchor_camera,
+                None => camera.is_active,
+            });
+        let (camera_entity, camera) = match camera {
+            Some(camera) => camera,
+            None => continue,
+        };
+        let (camera_transform, target) = match (
+            world_transform(camera_entity, &transform_query),
+            world_transform(anchor.target, &transform_query),
+        ) {
+            (Some(camera_transform), Some(target)) => (camera_transform, target),
+            _ => continue,
+        };
+
+        let world_pos = target.translation() + anchor.offset;
+        match camera.world_to_viewport(&camera_transform, world_pos) {
+            Some(screen_pos) => {
+                // center the node horizontally above the anchor point
+                let width = node.map_or(0., |node| node.size.x);
+                style.display = Display::Flex;
+                style.position = UiRect {
+                    left: Val::Px(screen_pos.x - width / 2.),
+                    bottom: Val::Px(screen_pos.y),
+                    ..default()
+                };
+            },
+            // behind the camera
+            None => style.display = Display::None,
+        }
+    }
+}
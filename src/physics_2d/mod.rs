@@ -4,13 +4,17 @@
 
 use bevy::prelude::*;
 #[cfg(feature = "serde")]
+use bevy::reflect::ReflectDeserialize;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// Resource to influence the gravity of all the entities in the world
 pub struct WorldGravity(pub Vec2);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Component, Clone)]
+#[derive(Component, Reflect, Clone)]
+#[cfg_attr(not(feature = "serde"), reflect_value(Component))]
+#[cfg_attr(feature = "serde", reflect_value(Component, Deserialize))]
 pub struct RigidBody {
     pub mass: f32,
     /// override the world's gravity
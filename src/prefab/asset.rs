@@ -0,0 +1,105 @@
+//! Load prefabs through bevy's asset server
+//!
+//! This allows prefab files to be hot reloaded while the game is running. Hot reloading must be
+//! enabled with `AssetServerSettings::watch_for_changes`.
+
+use std::marker::PhantomData;
+
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+};
+use serde::de::DeserializeOwned;
+
+use super::{parse_prefab_lib, PrefabLib};
+
+/// Prefab types that can be loaded through the asset server
+pub trait PrefabAsset: DeserializeOwned + TypeUuid + Send + Sync + 'static {}
+
+impl<P> PrefabAsset for P where P: DeserializeOwned + TypeUuid + Send + Sync + 'static {}
+
+/// Resource holding the handle to the loaded prefab library
+pub struct PrefabHandle<P: PrefabAsset>(pub Handle<PrefabLib<P>>);
+
+/// Event sent when a prefab library has been modified on disk and reloaded
+pub struct PrefabsReloaded<P: PrefabAsset> {
+    pub handle: Handle<PrefabLib<P>>,
+}
+
+/// Asset loader that parses a RON map of prefabs
+pub struct PrefabLoader<P> {
+    extensions: &'static [&'static str],
+    phantom: PhantomData<fn() -> P>,
+}
+
+impl<P: PrefabAsset> AssetLoader for PrefabLoader<P> {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let path = load_context.path().to_string_lossy().into_owned();
+            let lib = parse_prefab_lib::<P>(bytes, Some(&path))?;
+            load_context.set_default_asset(LoadedAsset::new(lib));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
+
+/// Plugin to load a prefab library from the asset server
+///
+/// Each prefab type should use a unique extension (such as `enemies.ron`), since the asset server
+/// can only have a single loader per extension.
+pub struct PrefabPlugin<P> {
+    path: String,
+    extensions: &'static [&'static str],
+    phantom: PhantomData<fn() -> P>,
+}
+
+impl<P> PrefabPlugin<P> {
+    /// Load the prefab library at `path`, relative to the assets folder
+    pub fn new(path: &str, extensions: &'static [&'static str]) -> Self {
+        PrefabPlugin {
+            path: path.to_owned(),
+            extensions,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<P: PrefabAsset> Plugin for PrefabPlugin<P> {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<PrefabLib<P>>()
+            .add_asset_loader(PrefabLoader::<P> {
+                extensions: self.extensions,
+                phantom: PhantomData,
+            })
+            .add_event::<PrefabsReloaded<P>>()
+            .add_system(prefab_reload_system::<P>);
+
+        let handle = app
+            .world
+            .resource::<AssetServer>()
+            .load::<PrefabLib<P>, _>(&self.path);
+        app.insert_resource(PrefabHandle(handle));
+    }
+}
+
+fn prefab_reload_system<P: PrefabAsset>(
+    mut asset_events: EventReader<AssetEvent<PrefabLib<P>>>,
+    mut writer: EventWriter<PrefabsReloaded<P>>,
+) {
+    for event in asset_events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            writer.send(PrefabsReloaded {
+                handle: handle.clone(),
+            });
+        }
+    }
+}
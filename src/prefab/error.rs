@@ -0,0 +1,39 @@
+//! Errors that can occur while loading prefabs
+
+use thiserror::Error;
+
+use super::PrefabId;
+
+#[derive(Error, Debug)]
+pub enum PrefabError {
+    #[error("failed to access prefab file {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error(
+        "{}:{line}:{column}: {msg}{}",
+        path.as_deref().unwrap_or("<ron string>"),
+        id.as_ref().map(|id| format!(" (in prefab '{}')", id)).unwrap_or_default()
+    )]
+    Parse {
+        /// File the prefab was loaded from, if any
+        path: Option<String>,
+        /// Prefab that was being parsed when the error occurred
+        id: Option<PrefabId>,
+        line: usize,
+        column: usize,
+        msg: String,
+    },
+    #[error(
+        "duplicate prefab id '{id}'{}",
+        path.as_ref().map(|path| format!(" in {}", path)).unwrap_or_default()
+    )]
+    DuplicateId {
+        id: PrefabId,
+        /// File the duplicate was found in, if any
+        path: Option<String>,
+    },
+    #[error("failed to write prefabs: {0}")]
+    Serialize(#[from] ron::Error),
+}
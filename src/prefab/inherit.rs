@@ -0,0 +1,1241 @@
+//! Prefab inheritance using an `extends` field
+//!
+//! A prefab written as a RON struct can specify `extends: "parent_id"` to start from all of the
+//! fields of another prefab and override only some of them. Fields are merged as raw RON text
+//! before being deserialized, so enums and nested structs are copied over as is.
+//!
+//! Errors are still reported against the line and column of the original file.
+
+use std::collections::HashMap;
+
+use serde::de::{DeserializeOwned, IgnoredAny};
+
+use super::{PrefabError, PrefabId, PrefabLib};
+
+const EXTENDS_FIELD: &str = "extends";
+
+/// Cheap check to see if the inheritance pass needs to run at all
+pub(super) fn uses_extends(src: &str) -> bool {
+    src.contains(EXTENDS_FIELD)
+}
+
+/// Parent and own fields of a prefab that uses `extends`
+///
+/// Kept so that saving a [PrefabLib] can write the prefab with its parent again.
+#[derive(Clone)]
+pub(super) struct Inherited {
+    /// Full id of the parent
+    pub(super) parent: PrefabId,
+    /// Fields set by the prefab itself
+    pub(super) fields: Vec<String>,
+}
+
+/// Prefab file passed to [parse_files]
+#[derive(Clone, Copy)]
+pub(super) struct SourceFile<'a> {
+    pub(super) src: &'a str,
+    pub(super) path: Option<&'a str>,
+    /// Prefix of the ids in this file, separated by a `/`
+    pub(super) namespace: Option<&'a str>,
+}
+
+/// Parse a map of prefabs, resolving inheritance
+///
+/// Returns `None` if the file should be parsed normally instead. This is the case when no prefab
+/// has an `extends` field, for example when the word only appears in a string or comment, or when
+/// the file is not valid RON so that RON can report the error. Valid files that can not be
+/// scanned are reported as an error, rather than silently ignoring their `extends` fields.
+pub(super) fn parse_with_inheritance<P: DeserializeOwned>(
+    src: &str,
+    path: Option<&str>,
+) -> Option<Result<PrefabLib<P>, PrefabError>> {
+    let file = match scan(src, path)? {
+        Ok(file) => file,
+        Err(err) => return Some(Err(err)),
+    };
+    if !has_extends(&file) {
+        return None;
+    }
+    let files = [ScannedFile {
+        source: SourceFile {
+            src,
+            path,
+            namespace: None,
+        },
+        file,
+    }];
+    Some(
+        Resolver::new(&files)
+            .resolve_all()
+            .map(|mut maps| maps.remove(0)),
+    )
+}
+
+/// Parse several prefab files whose prefabs may extend prefabs from the other files
+///
+/// Returns the prefabs of each file with their ids namespaced. A parent id is first looked up in
+/// the namespace of the prefab extending it and then as a full id, so `extends: "goblin"` and
+/// `extends: "enemies/goblin"` both work from `enemies.ron`.
+pub(super) fn parse_files<P: DeserializeOwned>(
+    sources: &[SourceFile],
+) -> Result<Vec<PrefabLib<P>>, PrefabError> {
+    let namespaced = |source: &SourceFile| -> Result<PrefabLib<P>, PrefabError> {
+        let lib = super::parse_prefab_lib(source.src.as_bytes(), source.path)?;
+        Ok(match source.namespace {
+            Some(namespace) => lib.namespaced(namespace),
+            None => lib,
+        })
+    };
+
+    // files that can not be scanned are parsed on their own, which reports any errors
+    let mut scanned = vec![];
+    let mut libs = vec![];
+    for source in sources.iter() {
+        let mut scanner = Scanner::new(source.src);
+        match scanner.file() {
+            Some(file) => {
+                scanned.push(ScannedFile {
+                    source: *source,
+                    file,
+                });
+                libs.push(None);
+            },
+            None => libs.push(Some(namespaced(source)?)),
+        }
+    }
+
+    let mut resolved = if scanned.iter().any(|f| has_extends(&f.file)) {
+        Resolver::new(&scanned).resolve_all()?.into_iter()
+    } else {
+        scanned
+            .iter()
+            .map(|f| namespaced(&f.source))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+    };
+    Ok(libs
+        .into_iter()
+        .map(|lib| lib.unwrap_or_else(|| resolved.next().unwrap_or_default()))
+        .collect())
+}
+
+/// Scan a file, see [parse_with_inheritance] for when `None` is returned
+fn scan(src: &str, path: Option<&str>) -> Option<Result<File, PrefabError>> {
+    let mut scanner = Scanner::new(src);
+    match scanner.file() {
+        Some(file) => Some(Ok(file)),
+        None if ron::from_str::<IgnoredAny>(src).is_err() => None,
+        None => {
+            let (line, column) = line_col(src, scanner.pos);
+            Some(Err(PrefabError::Parse {
+                path: path.map(str::to_owned),
+                id: None,
+                line,
+                column,
+                msg: "syntax not supported in files that use extends".to_owned(),
+            }))
+        },
+    }
+}
+
+fn has_extends(file: &File) -> bool {
+    file.entries.iter().any(|entry| {
+        matches!(
+            entry.body,
+            Body::Struct {
+                extends: Some(_),
+                ..
+            }
+        )
+    })
+}
+
+fn full_id(namespace: Option<&str>, id: &str) -> PrefabId {
+    match namespace {
+        Some(namespace) => format!("{}/{}", namespace, id),
+        None => id.to_owned(),
+    }
+}
+
+/// Write prefabs that use `extends` with their parent again
+///
+/// Takes the pretty printed RON of a whole [PrefabLib] and keeps only the fields each prefab sets
+/// itself or that differ from its parent. Prefabs whose parent is no longer in the output, or that
+/// are not written as structs, are left flattened.
+pub(super) fn write_inherited(
+    src: &str,
+    inherited: &HashMap<PrefabId, Inherited>,
+) -> Result<String, ron::Error> {
+    let file = match Scanner::new(src).file() {
+        Some(file) => file,
+        None => return Ok(src.to_owned()),
+    };
+    let struct_fields = |entry: &Entry| match &entry.body {
+        Body::Struct { fields, .. } if !fields.is_empty() => Some(fields.clone()),
+        _ => None,
+    };
+    let text = |field: &Field| &src[field.span.start..field.span.end];
+
+    let mut out = String::new();
+    let mut pos = 0;
+    for entry in file.entries.iter() {
+        let inherited = match inherited.get(&entry.id) {
+            Some(inherited) => inherited,
+            None => continue,
+        };
+        let parent_fields = file
+            .entries
+            .iter()
+            .find(|e| e.id == inherited.parent)
+            .and_then(struct_fields);
+        let (fields, parent_fields) = match (struct_fields(entry), parent_fields) {
+            (Some(fields), Some(parent_fields)) => (fields, parent_fields),
+            _ => continue,
+        };
+
+        // extends goes on its own line before the first field, with the same indentation
+        let first = line_start(src, fields[0].span.start);
+        out.push_str(&src[pos..first]);
+        out.push_str(&src[first..fields[0].span.start]);
+        out.push_str(&format!(
+            "{}: {},\n",
+            EXTENDS_FIELD,
+            ron::to_string(&inherited.parent)?
+        ));
+        pos = first;
+
+        for field in fields.iter() {
+            let same_as_parent = parent_fields
+                .iter()
+                .any(|p| p.name == field.name && text(p) == text(field));
+            if same_as_parent && !inherited.fields.contains(&field.name) {
+                out.push_str(&src[pos..line_start(src, field.span.start)]);
+                pos = src[field.span.end..]
+                    .find('\n')
+                    .map_or(src.len(), |i| field.span.end + i + 1);
+            }
+        }
+    }
+    out.push_str(&src[pos..]);
+    Ok(out)
+}
+
+fn line_start(src: &str, offset: usize) -> usize {
+    src[..offset].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// Find the line and column of every element in a list field of a RON struct
+///
+/// Used to report positions for values that are only checked after deserializing. Returns `None`
+/// if the source could not be scanned or has no such field.
+pub(crate) fn list_positions(src: &str, field: &str) -> Option<Vec<(usize, usize)>> {
+    let mut scanner = Scanner::new(src);
+    scanner.skip_ws();
+    while scanner.rest().starts_with("#!") {
+        scanner.pos += 2;
+        scanner.group()?;
+        scanner.skip_ws();
+    }
+
+    let span = scanner.value()?;
+    let mut inner = Scanner {
+        src: &src[..span.end],
+        pos: span.start,
+    };
+    let field = match inner.struct_body()? {
+        Body::Struct { fields, .. } => fields.into_iter().find(|f| f.name == field)?,
+        Body::Other(_) => return None,
+    };
+
+    let mut list = Scanner {
+        src: &src[..field.span.end],
+        pos: field.span.start,
+    };
+    list.ident()?;
+    list.eat(':')?;
+    list.eat('[')?;
+    let mut positions = vec![];
+    loop {
+        list.skip_ws();
+        if list.peek()? == ']' {
+            break;
+        }
+        positions.push(line_col(src, list.pos));
+        list.value()?;
+        list.skip_ws();
+        if list.peek()? == ',' {
+            list.bump();
+        }
+    }
+    Some(positions)
+}
+
+/// Slice of the original source
+#[derive(Clone, Copy)]
+struct Span {
+    start: usize,
+    end: usize,
+}
+
+struct Entry {
+    id: PrefabId,
+    id_span: Span,
+    body: Body,
+}
+
+enum Body {
+    Struct {
+        name: Option<Span>,
+        fields: Vec<Field>,
+        extends: Option<(PrefabId, Span)>,
+    },
+    Other(Span),
+}
+
+#[derive(Clone)]
+struct Field {
+    name: String,
+    /// Whole field including the name, such as `hp: 10`
+    span: Span,
+}
+
+struct File {
+    /// Extension attributes such as `#![enable(implicit_some)]`
+    header: Span,
+    entries: Vec<Entry>,
+}
+
+struct Scanner<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(src: &'a str) -> Self {
+        Scanner { src, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> Option<()> {
+        self.skip_ws();
+        if self.peek()? == c {
+            self.bump();
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("//") {
+                self.pos += rest.find('\n').unwrap_or(rest.len());
+            } else if let Some(comment) = rest.strip_prefix("/*") {
+                self.pos += comment.find("*/").map_or(rest.len(), |i| i + 4);
+            } else if matches!(self.peek(), Some(c) if c.is_whitespace()) {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn file(&mut self) -> Option<File> {
+        self.skip_ws();
+        let header_start = self.pos;
+        while self.rest().starts_with("#!") {
+            self.pos += 2;
+            self.group()?;
+            self.skip_ws();
+        }
+        let header = Span {
+            start: header_start,
+            end: self.pos,
+        };
+
+        self.eat('{')?;
+        let mut entries = vec![];
+        loop {
+            self.skip_ws();
+            if self.peek()? == '}' {
+                self.bump();
+                break;
+            }
+            entries.push(self.entry()?);
+            self.skip_ws();
+            if self.peek()? == ',' {
+                self.bump();
+            }
+        }
+
+        self.skip_ws();
+        if !self.rest().is_empty() {
+            return None;
+        }
+        Some(File { header, entries })
+    }
+
+    fn entry(&mut self) -> Option<Entry> {
+        let id_span = self.value()?;
+        let id = match ron::from_str::<PrefabId>(&self.src[id_span.start..id_span.end]) {
+            Ok(id) => id,
+            Err(_) => {
+                // point errors at the start of the id
+                self.pos = id_span.start;
+                return None;
+            },
+        };
+        self.eat(':')?;
+        self.skip_ws();
+        let body = self.body()?;
+        Some(Entry { id, id_span, body })
+    }
+
+    fn body(&mut self) -> Option<Body> {
+        let start = self.pos;
+        let span = self.value()?;
+        let mut inner = Scanner {
+            src: &self.src[..span.end],
+            pos: start,
+        };
+        Some(inner.struct_body().unwrap_or(Body::Other(span)))
+    }
+
+    fn struct_body(&mut self) -> Option<Body> {
+        let name = self.ident();
+        self.eat('(')?;
+
+        let mut fields: Vec<Field> = vec![];
+        let mut extends = None;
+        loop {
+            self.skip_ws();
+            if self.peek()? == ')' {
+                self.bump();
+                break;
+            }
+
+            let start = self.pos;
+            let field_name = self.ident()?;
+            self.eat(':')?;
+            self.skip_ws();
+            let value = self.value()?;
+            let field_name = self.src[field_name.start..field_name.end].to_owned();
+
+            if field_name == EXTENDS_FIELD {
+                let parent = ron::from_str::<PrefabId>(&self.src[value.start..value.end]).ok()?;
+                extends = Some((parent, value));
+            } else {
+                fields.push(Field {
+                    name: field_name,
+                    span: Span {
+                        start,
+                        end: value.end,
+                    },
+                });
+            }
+
+            self.skip_ws();
+            if self.peek()? == ',' {
+                self.bump();
+            }
+        }
+
+        self.skip_ws();
+        if !self.rest().is_empty() {
+            return None;
+        }
+        Some(Body::Struct {
+            name,
+            fields,
+            extends,
+        })
+    }
+
+    fn ident(&mut self) -> Option<Span> {
+        self.skip_ws();
+        let start = self.pos;
+        // raw identifiers such as r#type
+        if self.rest().starts_with("r#") {
+            self.pos += 2;
+        }
+        while matches!(self.peek(), Some(c) if c.is_alphanumeric() || c == '_') {
+            self.bump();
+        }
+        (self.pos > start).then_some(Span {
+            start,
+            end: self.pos,
+        })
+    }
+
+    /// Skip over a single value, stopping at a comma or closing bracket
+    fn value(&mut self) -> Option<Span> {
+        self.skip_ws();
+        let start = self.pos;
+        let mut end = self.pos;
+        loop {
+            match self.peek() {
+                None | Some(',' | ':' | ')' | ']' | '}') => break,
+                Some('(' | '[' | '{') => self.group()?,
+                Some(_) => self.token()?,
+            }
+            end = self.pos;
+            self.skip_ws();
+        }
+        (end > start).then_some(Span { start, end })
+    }
+
+    /// Skip over a bracketed group including the brackets
+    fn group(&mut self) -> Option<()> {
+        let close = match self.bump()? {
+            '(' => ')',
+            '[' => ']',
+            '{' => '}',
+            _ => return None,
+        };
+        loop {
+            self.skip_ws();
+            match self.peek()? {
+                c if c == close => {
+                    self.bump();
+                    return Some(());
+                },
+                '(' | '[' | '{' => self.group()?,
+                ')' | ']' | '}' => return None,
+                _ => self.token()?,
+            }
+        }
+    }
+
+    /// Skip over a string, char, raw string or any other single character
+    fn token(&mut self) -> Option<()> {
+        let rest = self.rest();
+        if rest.starts_with('"') {
+            self.bump();
+            loop {
+                match self.bump()? {
+                    '\\' => {
+                        self.bump()?;
+                    },
+                    '"' => break,
+                    _ => {},
+                }
+            }
+        } else if rest
+            .strip_prefix('r')
+            .is_some_and(|raw| raw.trim_start_matches('#').starts_with('"'))
+        {
+            self.bump();
+            let hashes = self.rest().chars().take_while(|c| *c == '#').count();
+            let terminator = format!("\"{}", "#".repeat(hashes));
+            self.pos += hashes + 1;
+            self.pos += self.rest().find(&terminator)? + terminator.len();
+        } else if rest.starts_with('\'') {
+            self.bump();
+            if self.bump()? == '\\' {
+                self.bump()?;
+            }
+            while self.bump()? != '\'' {}
+        } else {
+            self.bump();
+        }
+        Some(())
+    }
+}
+
+/// Maps lines of generated RON back to the original source
+struct Segment {
+    /// Index of the file the lines come from
+    file: usize,
+    gen_line: usize,
+    line_count: usize,
+    offset: usize,
+}
+
+struct ScannedFile<'a> {
+    source: SourceFile<'a>,
+    file: File,
+}
+
+/// Struct name and fields of a prefab, along with the index of the file they come from
+type Resolved = (Option<(usize, Span)>, Vec<(usize, Field)>);
+
+struct Resolver<'a> {
+    files: &'a [ScannedFile<'a>],
+    index: HashMap<PrefabId, (usize, &'a Entry)>,
+    resolved: HashMap<PrefabId, Resolved>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(files: &'a [ScannedFile<'a>]) -> Self {
+        Resolver {
+            files,
+            index: HashMap::new(),
+            resolved: HashMap::new(),
+        }
+    }
+
+    fn resolve_all<P: DeserializeOwned>(mut self) -> Result<Vec<PrefabLib<P>>, PrefabError> {
+        let files = self.files;
+        for (i, file) in files.iter().enumerate() {
+            for entry in file.file.entries.iter() {
+                let id = file.id(entry);
+                match self.index.insert(id.clone(), (i, entry)) {
+                    Some((other, _)) if other == i => {
+                        return Err(self.error(
+                            i,
+                            entry.id_span.start,
+                            Some(&id),
+                            format!("duplicate prefab id '{}'", entry.id),
+                        ))
+                    },
+                    Some(_) => {
+                        return Err(PrefabError::DuplicateId {
+                            id,
+                            path: file.source.path.map(str::to_owned),
+                        })
+                    },
+                    None => {},
+                }
+            }
+        }
+
+        let mut libs = vec![];
+        for (i, file) in files.iter().enumerate() {
+            let mut lib = PrefabLib::default();
+            for entry in file.file.entries.iter() {
+                let id = file.id(entry);
+                if let Body::Struct {
+                    fields,
+                    extends: Some((parent, _)),
+                    ..
+                } = &entry.body
+                {
+                    let inherited = Inherited {
+                        parent: self.parent_id(i, parent).unwrap_or_else(|| parent.clone()),
+                        fields: fields.iter().map(|field| field.name.clone()).collect(),
+                    };
+                    lib.inherited.insert(id.clone(), inherited);
+                }
+
+                let prefab = match &entry.body {
+                    Body::Other(span) => {
+                        let segments = vec![Segment {
+                            file: i,
+                            gen_line: 1,
+                            line_count: usize::MAX,
+                            offset: span.start,
+                        }];
+                        let body = &file.source.src[span.start..span.end];
+                        self.deserialize(i, body, &segments, &id, entry)?
+                    },
+                    Body::Struct { .. } => {
+                        let (name, fields) = self.resolve(&id, &mut vec![])?;
+                        let (text, segments) = self.generate(name, &fields);
+                        self.deserialize(i, &text, &segments, &id, entry)?
+                    },
+                };
+                lib.map.insert(id, prefab);
+            }
+            libs.push(lib);
+        }
+        Ok(libs)
+    }
+
+    fn resolve(&mut self, id: &str, stack: &mut Vec<PrefabId>) -> Result<Resolved, PrefabError> {
+        if let Some(resolved) = self.resolved.get(id) {
+            return Ok(resolved.clone());
+        }
+
+        let (i, entry) = self.index[id];
+        let (name, fields, extends) = match &entry.body {
+            Body::Struct {
+                name,
+                fields,
+                extends,
+            } => (*name, fields, extends),
+            Body::Other(span) => {
+                return Err(self.error(
+                    i,
+                    span.start,
+                    Some(id),
+                    "only struct prefabs can be extended".to_owned(),
+                ))
+            },
+        };
+        let fields = fields.iter().map(|field| (i, field.clone()));
+
+        stack.push(id.to_owned());
+        let resolved = match extends {
+            None => (name.map(|name| (i, name)), fields.collect()),
+            Some((parent, span)) => {
+                let parent = match self.parent_id(i, parent) {
+                    Some(parent) => parent,
+                    None => {
+                        return Err(self.error(
+                            i,
+                            span.start,
+                            Some(id),
+                            format!("unknown parent prefab '{}'", parent),
+                        ))
+                    },
+                };
+                if stack.contains(&parent) {
+                    stack.push(parent);
+                    return Err(self.error(
+                        i,
+                        span.start,
+                        Some(id),
+                        format!("inheritance cycle {}", stack.join(" -> ")),
+                    ));
+                }
+
+                let (parent_name, mut merged) = self.resolve(&parent, stack)?;
+                for field in fields {
+                    match merged.iter_mut().find(|(_, f)| f.name == field.1.name) {
+                        Some(existing) => *existing = field,
+                        None => merged.push(field),
+                    }
+                }
+                (name.map(|name| (i, name)).or(parent_name), merged)
+            },
+        };
+        stack.pop();
+
+        self.resolved.insert(id.to_owned(), resolved.clone());
+        Ok(resolved)
+    }
+
+    /// Find the full id of a parent, preferring prefabs in the same namespace
+    fn parent_id(&self, file: usize, parent: &str) -> Option<PrefabId> {
+        let namespace = self.files[file].source.namespace;
+        [full_id(namespace, parent), parent.to_owned()]
+            .into_iter()
+            .find(|id| self.index.contains_key(id))
+    }
+
+    /// Build the RON text of a struct, one field per line
+    fn generate(
+        &self,
+        name: Option<(usize, Span)>,
+        fields: &[(usize, Field)],
+    ) -> (String, Vec<Segment>) {
+        let mut text = String::new();
+        if let Some((file, name)) = name {
+            text.push_str(&self.files[file].source.src[name.start..name.end]);
+        }
+        text.push_str("(\n");
+
+        let mut segments = vec![];
+        let mut gen_line = 2;
+        for (file, field) in fields.iter() {
+            let raw = &self.files[*file].source.src[field.span.start..field.span.end];
+            let line_count = raw.lines().count().max(1);
+            segments.push(Segment {
+                file: *file,
+                gen_line,
+                line_count,
+                offset: field.span.start,
+            });
+            text.push_str(raw);
+            // newline ensures trailing comments don't swallow the comma
+            text.push_str("\n,\n");
+            gen_line += line_count + 1;
+        }
+        text.push(')');
+
+        (text, segments)
+    }
+
+    fn deserialize<P: DeserializeOwned>(
+        &self,
+        file: usize,
+        body: &str,
+        segments: &[Segment],
+        id: &str,
+        entry: &Entry,
+    ) -> Result<P, PrefabError> {
+        let source = &self.files[file];
+        let header = &source.source.src[source.file.header.start..source.file.header.end];
+        let header_lines = header.matches('\n').count();
+        let text = format!("{}\n{}", header, body);
+
+        ron::from_str::<P>(&text).map_err(|e| {
+            let line = e.position.line.saturating_sub(header_lines + 1);
+            let segment = segments
+                .iter()
+                .find(|s| s.gen_line <= line && line - s.gen_line < s.line_count);
+
+            let (file, line, column) = match segment {
+                Some(segment) => {
+                    let src = self.files[segment.file].source.src;
+                    let (orig_line, orig_col) = line_col(src, segment.offset);
+                    if line == segment.gen_line {
+                        (segment.file, orig_line, orig_col + e.position.col - 1)
+                    } else {
+                        (
+                            segment.file,
+                            orig_line + line - segment.gen_line,
+                            e.position.col,
+                        )
+                    }
+                },
+                None => {
+                    let (line, column) = line_col(source.source.src, entry.id_span.start);
+                    (file, line, column)
+                },
+            };
+
+            PrefabError::Parse {
+                path: self.files[file].source.path.map(str::to_owned),
+                id: Some(id.to_owned()),
+                line,
+                column,
+                msg: e.code.to_string(),
+            }
+        })
+    }
+
+    fn error(&self, file: usize, offset: usize, id: Option<&str>, msg: String) -> PrefabError {
+        let source = &self.files[file].source;
+        let (line, column) = line_col(source.src, offset);
+        PrefabError::Parse {
+            path: source.path.map(str::to_owned),
+            id: id.map(str::to_owned),
+            line,
+            column,
+            msg,
+        }
+    }
+}
+
+impl<'a> ScannedFile<'a> {
+    fn id(&self, entry: &Entry) -> PrefabId {
+        full_id(self.source.namespace, &entry.id)
+    }
+}
+
+/// Convert a byte offset to a one based line and column
+fn line_col(src: &str, offset: usize) -> (usize, usize) {
+    let before = &src[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = offset - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::{super::PrefabMap, *};
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Enemy {
+        hp: u32,
+        speed: f32,
+        stats: Stats,
+        kind: Kind,
+        name: String,
+        #[serde(default)]
+        symbol: Option<char>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Stats {
+        attack: u32,
+        defense: u32,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum Kind {
+        Melee,
+        Ranged { range: f32 },
+    }
+
+    fn parse(src: &str) -> Result<PrefabMap<Enemy>, PrefabError> {
+        match parse_with_inheritance(src, None) {
+            Some(result) => result.map(|lib| lib.map),
+            None => panic!("inheritance pass did not run"),
+        }
+    }
+
+    /// Returns the id, line, column and message of a parse error
+    fn parse_err(src: &str) -> (Option<PrefabId>, usize, usize, String) {
+        match parse(src) {
+            Err(PrefabError::Parse {
+                id,
+                line,
+                column,
+                msg,
+                ..
+            }) => (id, line, column, msg),
+            Err(err) => panic!("unexpected error {}", err),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
+    const BASE: &str = r#"
+    "base": Enemy(
+        hp: 10,
+        speed: 1.5,
+        stats: Stats(attack: 1, defense: 2),
+        kind: Ranged(range: 5.0),
+        name: "base",
+    ),"#;
+
+    #[test]
+    fn override_nested_struct_and_enum() {
+        let src = format!(
+            r#"{{{}
+    "brute": (
+        extends: "base",
+        hp: 30,
+        stats: (attack: 5, defense: 0),
+        kind: Melee,
+    ),
+}}"#,
+            BASE
+        );
+        let map = parse(&src).unwrap();
+
+        let brute = &map["brute"];
+        assert_eq!(brute.hp, 30);
+        assert_eq!(brute.speed, 1.5);
+        // nested structs are replaced as a whole rather than merged
+        assert_eq!(
+            brute.stats,
+            Stats {
+                attack: 5,
+                defense: 0
+            }
+        );
+        assert_eq!(brute.kind, Kind::Melee);
+        assert_eq!(brute.name, "base");
+        assert_eq!(map["base"].kind, Kind::Ranged { range: 5.0 });
+    }
+
+    #[test]
+    fn multi_level_inheritance() {
+        let src = format!(
+            r#"{{{}
+    "archer": (extends: "base", speed: 2.0),
+    "elite_archer": (extends: "archer", hp: 50),
+}}"#,
+            BASE
+        );
+        let map = parse(&src).unwrap();
+
+        let elite = &map["elite_archer"];
+        assert_eq!((elite.hp, elite.speed), (50, 2.0));
+        assert_eq!(elite.kind, Kind::Ranged { range: 5.0 });
+    }
+
+    #[test]
+    fn raw_strings_and_chars() {
+        let src = r##"{
+    "base": Enemy(
+        hp: 10,
+        speed: 1.5,
+        stats: Stats(attack: 1, defense: 2),
+        kind: Melee,
+        name: r#"a "quoted", name)"#,
+        symbol: Some('}'),
+    ),
+    "child": (extends: "base", name: r"extends: ("),
+}"##;
+        let map = parse(src).unwrap();
+
+        assert_eq!(map["base"].name, r#"a "quoted", name)"#);
+        assert_eq!(map["child"].name, "extends: (");
+        assert_eq!(map["child"].symbol, Some('}'));
+    }
+
+    #[test]
+    fn comments_mentioning_extends() {
+        let src = format!(
+            r#"{{{}
+    // extends: "nothing", this comment is not a field
+    "child": (
+        /* extends: "ghost" */
+        extends: "base", // extends
+        hp: 20, /* ) */
+    ),
+}}"#,
+            BASE
+        );
+        let map = parse(&src).unwrap();
+        assert_eq!(map["child"].hp, 20);
+        assert_eq!(map["child"].name, "base");
+    }
+
+    #[test]
+    fn extends_only_in_comments_and_strings() {
+        let src = format!(
+            r#"{{{}
+    // extends: "base"
+    "other": Enemy(
+        hp: 1,
+        speed: 1.0,
+        stats: Stats(attack: 0, defense: 0),
+        kind: Melee,
+        name: "extends: "base"",
+    ),
+}}"#,
+            BASE
+        );
+        assert!(uses_extends(&src));
+        assert!(parse_with_inheritance::<Enemy>(&src, None).is_none());
+    }
+
+    #[test]
+    fn raw_identifiers() {
+        #[derive(Deserialize, Debug)]
+        struct Keyword {
+            r#type: u32,
+            #[serde(default)]
+            name: String,
+        }
+
+        let src = r#"{
+    "base": (r#type: 1, name: "base"),
+    "child": (extends: "base", r#type: 2),
+}"#;
+        let map = parse_with_inheritance::<Keyword>(src, None)
+            .unwrap()
+            .unwrap()
+            .map;
+        assert_eq!(map["child"].r#type, 2);
+        assert_eq!(map["child"].name, "base");
+    }
+
+    #[test]
+    fn unscannable_file_with_extends() {
+        // bare identifier keys are valid RON but not prefab ids the scanner understands
+        let src = r#"{
+    base: (hp: 1),
+    child: (extends: "base"),
+}"#;
+        match parse_with_inheritance::<Enemy>(src, None) {
+            Some(Err(PrefabError::Parse { line, column, .. })) => {
+                assert_eq!((line, column), (2, 5))
+            },
+            _ => panic!("expected a parse error"),
+        }
+
+        // invalid RON is left to the normal parser for a better error message
+        let src = r#"{ "child": (extends: "base", hp: ) }"#;
+        assert!(parse_with_inheritance::<Enemy>(src, None).is_none());
+    }
+
+    #[test]
+    fn unknown_parent() {
+        let src = format!(
+            r#"{{{}
+    "child": (extends: "ghost", hp: 20),
+}}"#,
+            BASE
+        );
+        let (id, line, column, msg) = parse_err(&src);
+        assert_eq!(id.as_deref(), Some("child"));
+        assert_eq!((line, column), (9, 24));
+        assert_eq!(msg, "unknown parent prefab 'ghost'");
+    }
+
+    #[test]
+    fn self_extension() {
+        let src = r#"{
+    "loop": (extends: "loop", hp: 20),
+}"#;
+        let (id, line, column, msg) = parse_err(src);
+        assert_eq!(id.as_deref(), Some("loop"));
+        assert_eq!((line, column), (2, 23));
+        assert_eq!(msg, "inheritance cycle loop -> loop");
+    }
+
+    #[test]
+    fn inheritance_cycle() {
+        let src = r#"{
+    "a": (extends: "b"),
+    "b": (extends: "a"),
+}"#;
+        let (_, _, _, msg) = parse_err(src);
+        assert_eq!(msg, "inheritance cycle a -> b -> a");
+    }
+
+    #[test]
+    fn error_in_overridden_field() {
+        let src = format!(
+            r#"{{{}
+    "child": (
+        extends: "base",
+        hp: "lots",
+    ),
+}}"#,
+            BASE
+        );
+        let (id, line, column, _) = parse_err(&src);
+        assert_eq!(id.as_deref(), Some("child"));
+        assert_eq!((line, column), (11, 13));
+    }
+
+    #[test]
+    fn error_in_inherited_field() {
+        // the child comes first so its error is reported before the parent's
+        let src = r#"{
+    "child": (extends: "base", hp: 20),
+    "base": Enemy(
+        hp: 10,
+        speed: "fast",
+        stats: Stats(attack: 1, defense: 2),
+        kind: Melee,
+        name: "base",
+    ),
+}"#;
+        let (id, line, column, _) = parse_err(src);
+        assert_eq!(id.as_deref(), Some("child"));
+        assert_eq!((line, column), (5, 16));
+    }
+
+    #[test]
+    fn error_position_after_header() {
+        let src = r#"#![enable(implicit_some)]
+{
+    "base": Enemy(
+        hp: 10,
+        speed: 1.5,
+        stats: Stats(attack: 1, defense: 2),
+        kind: Melee,
+        name: "base",
+        symbol: 'x',
+    ),
+    "child": (
+        extends: "base",
+        kind: Flying,
+    ),
+}"#;
+        let (id, line, column, _) = parse_err(src);
+        assert_eq!(id.as_deref(), Some("child"));
+        // RON reports unknown variants at the end of the identifier
+        assert_eq!((line, column), (13, 21));
+    }
+
+    #[test]
+    fn parent_in_other_file() {
+        let base = format!("{{{}\n}}", BASE);
+        let elite = r#"{
+    "elite": (extends: "enemies/base", hp: 50),
+    "boss": (extends: "elite", name: "boss"),
+}"#;
+        let sources = [
+            SourceFile {
+                src: &base,
+                path: Some("enemies.ron"),
+                namespace: Some("enemies"),
+            },
+            SourceFile {
+                src: elite,
+                path: Some("enemies/elite.ron"),
+                namespace: Some("enemies/elite"),
+            },
+        ];
+        let libs = parse_files::<Enemy>(&sources).unwrap();
+        let maps = libs.iter().map(|lib| &lib.map).collect::<Vec<_>>();
+        assert_eq!(maps[1]["enemies/elite/elite"].hp, 50);
+        assert_eq!(maps[1]["enemies/elite/elite"].name, "base");
+        assert_eq!(maps[1]["enemies/elite/boss"].hp, 50);
+        assert_eq!(maps[1]["enemies/elite/boss"].name, "boss");
+    }
+
+    #[test]
+    fn error_in_inherited_field_of_other_file() {
+        let base = r#"{
+    "base": Enemy(
+        hp: -1,
+        speed: 1.5,
+        stats: Stats(attack: 1, defense: 2),
+        kind: Melee,
+        name: "base",
+    ),
+}"#;
+        let sources = [
+            SourceFile {
+                src: base,
+                path: Some("base.ron"),
+                namespace: Some("base"),
+            },
+            SourceFile {
+                src: r#"{ "child": (extends: "base/base") }"#,
+                path: Some("child.ron"),
+                namespace: Some("child"),
+            },
+        ];
+        match parse_files::<Enemy>(&sources) {
+            Err(PrefabError::Parse { path, id, line, .. }) => {
+                assert_eq!(path.as_deref(), Some("base.ron"));
+                assert_eq!(id.as_deref(), Some("base/base"));
+                assert_eq!(line, 3);
+            },
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn save_keeps_extends() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Unit {
+            hp: u32,
+            speed: f32,
+            name: String,
+        }
+
+        let src = r#"{
+    "base": (hp: 10, speed: 1.0, name: "base"),
+    "child": (extends: "base", hp: 20),
+    "twin": (extends: "base", hp: 10),
+}"#;
+        let mut lib = PrefabLib::<Unit>::try_new(src).unwrap();
+        lib.update("base", |base| base.speed = 2.0);
+        lib.update("child", |child| child.name = "child".to_owned());
+
+        let saved = lib.to_ron_string().unwrap();
+        assert_eq!(
+            saved,
+            r#"{
+    "base": (
+        hp: 10,
+        speed: 2.0,
+        name: "base",
+    ),
+    "child": (
+        extends: "base",
+        hp: 20,
+        speed: 1.0,
+        name: "child",
+    ),
+    "twin": (
+        extends: "base",
+        hp: 10,
+        speed: 1.0,
+    ),
+}"#
+        );
+
+        let loaded = PrefabLib::<Unit>::try_new(&saved).unwrap();
+        for id in ["base", "child", "twin"] {
+            assert_eq!(loaded.get(id), lib.get(id));
+        }
+    }
+}
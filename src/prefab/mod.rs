@@ -1,33 +1,404 @@
 //! Load prefabs from RON files
 
+pub mod asset;
+pub mod error;
+pub(crate) mod inherit;
 pub mod models;
+#[cfg(feature = "serde")]
+pub mod reflect;
+pub mod spawn;
+pub mod tag;
+pub mod validate;
 
-use std::{collections::HashMap, fs, path::Path};
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    fmt, fs,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
 
-use serde::de::DeserializeOwned;
+use bevy::reflect::TypeUuid;
+use indexmap::IndexMap;
+use rand::{seq::SliceRandom, thread_rng};
+use ron::ser::PrettyConfig;
+use serde::{
+    de::{DeserializeOwned, DeserializeSeed, Error, MapAccess, Visitor},
+    ser::SerializeMap,
+    Serialize, Serializer,
+};
+
+pub use self::error::PrefabError;
+use self::inherit::Inherited;
 
 /// Type used to index prefabs
 pub type PrefabId = String;
 
-type PrefabMap<P> = HashMap<PrefabId, P>;
+/// Prefabs are kept in the order they were defined so that saved files stay readable
+type PrefabMap<P> = IndexMap<PrefabId, P>;
 
 /// Resource to store prefabs
+///
+/// Can also be used as an asset, see [asset::PrefabPlugin].
+#[derive(TypeUuid)]
+#[uuid = "b322606b-440c-45dc-b3c8-17d0b798a3fa"]
 pub struct PrefabLib<P: DeserializeOwned> {
     map: PrefabMap<P>,
+    inherited: HashMap<PrefabId, Inherited>,
 }
 
 impl<P: DeserializeOwned> PrefabLib<P> {
+    /// Parse prefabs from a RON string
+    ///
+    /// Panics if the string could not be parsed, use [PrefabLib::try_new] to handle the error.
     pub fn new(ron_string: &str) -> Self {
-        let map: PrefabMap<P> = ron::from_str(ron_string).unwrap();
-
-        PrefabLib { map }
+        PrefabLib::try_new(ron_string).unwrap_or_else(|e| panic!("{}", e))
     }
+
+    /// Load prefabs from a RON file
+    ///
+    /// Panics if the file could not be read or parsed, use [PrefabLib::try_from_file] to handle
+    /// the error.
     pub fn from_file(filepath: &str) -> Self {
-        let ron_string = fs::read_to_string(Path::new(&filepath)).unwrap();
-        PrefabLib::<P>::new(&ron_string)
+        PrefabLib::try_from_file(filepath).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Parse prefabs from a RON string
+    pub fn try_new(ron_string: &str) -> Result<Self, PrefabError> {
+        parse_prefab_lib(ron_string.as_bytes(), None)
+    }
+
+    /// Load prefabs from a RON file
+    pub fn try_from_file(filepath: &str) -> Result<Self, PrefabError> {
+        let ron_string =
+            fs::read_to_string(Path::new(&filepath)).map_err(|source| PrefabError::Io {
+                path: filepath.to_owned(),
+                source,
+            })?;
+        parse_prefab_lib(ron_string.as_bytes(), Some(filepath))
+    }
+
+    /// Load every `.ron` file in a directory and its subdirectories
+    ///
+    /// Prefab ids are namespaced by their path relative to the directory, so the prefab `goblin`
+    /// in `enemies/forest.ron` will have the id `enemies/forest/goblin`.
+    ///
+    /// Prefabs can extend prefabs from other files using their full id, such as
+    /// `extends: "enemies/forest/goblin"`. Ids without a namespace are looked up in the same file
+    /// first.
+    pub fn try_from_dir(dirpath: &str) -> Result<Self, PrefabError> {
+        let root = Path::new(dirpath);
+        let mut files = vec![];
+        find_ron_files(root, &mut files)?;
+        files.sort();
+
+        let mut sources = vec![];
+        for file in files.iter() {
+            let filepath = file.to_string_lossy().into_owned();
+            let namespace = file
+                .strip_prefix(root)
+                .unwrap_or(file)
+                .with_extension("")
+                .iter()
+                .map(|c| c.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let ron_string = fs::read_to_string(file).map_err(|source| PrefabError::Io {
+                path: filepath.clone(),
+                source,
+            })?;
+            sources.push((ron_string, filepath, namespace));
+        }
+
+        let sources = sources
+            .iter()
+            .map(|(src, path, namespace)| inherit::SourceFile {
+                src,
+                path: Some(path),
+                namespace: Some(namespace),
+            })
+            .collect::<Vec<_>>();
+        let mut lib = PrefabLib::default();
+        for (file_lib, source) in inherit::parse_files(&sources)?
+            .into_iter()
+            .zip(sources.iter())
+        {
+            lib.merge(file_lib).map_err(|e| match e {
+                PrefabError::DuplicateId { id, .. } => PrefabError::DuplicateId {
+                    id,
+                    path: source.path.map(str::to_owned),
+                },
+                e => e,
+            })?;
+        }
+        Ok(lib)
+    }
+
+    /// Prefix all prefab ids with a namespace, separated by a `/`
+    pub fn namespaced(self, namespace: &str) -> Self {
+        let map = self
+            .map
+            .into_iter()
+            .map(|(id, prefab)| (format!("{}/{}", namespace, id), prefab))
+            .collect();
+        let inherited = self
+            .inherited
+            .into_iter()
+            .map(|(id, inherited)| {
+                let parent = format!("{}/{}", namespace, inherited.parent);
+                (
+                    format!("{}/{}", namespace, id),
+                    Inherited {
+                        parent,
+                        ..inherited
+                    },
+                )
+            })
+            .collect();
+        PrefabLib { map, inherited }
+    }
+
+    /// Add all prefabs from another library
+    ///
+    /// Fails without modifying this library if any of the ids already exist.
+    pub fn merge(&mut self, other: PrefabLib<P>) -> Result<(), PrefabError> {
+        if let Some(id) = other.map.keys().find(|id| self.map.contains_key(*id)) {
+            return Err(PrefabError::DuplicateId {
+                id: id.clone(),
+                path: None,
+            });
+        }
+        self.map.extend(other.map);
+        self.inherited.extend(other.inherited);
+        Ok(())
+    }
+
+    /// Add all prefabs from another library, replacing any prefabs that already exist
+    ///
+    /// Useful for mods that want to override base game content.
+    pub fn extend(&mut self, other: PrefabLib<P>) {
+        for id in other.map.keys() {
+            self.inherited.remove(id);
+        }
+        self.map.extend(other.map);
+        self.inherited.extend(other.inherited);
     }
 
     pub fn get(&self, id: &str) -> Option<&P> {
         self.map.get(id)
     }
+
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut P> {
+        self.map.get_mut(id)
+    }
+
+    /// Add a prefab, returning the previous prefab with the same id
+    ///
+    /// New prefabs are added to the end, replaced prefabs keep their position.
+    pub fn insert(&mut self, id: impl Into<PrefabId>, prefab: P) -> Option<P> {
+        self.map.insert(id.into(), prefab)
+    }
+
+    /// Remove a prefab, keeping the order of the remaining prefabs
+    pub fn remove(&mut self, id: &str) -> Option<P> {
+        self.inherited.remove(id);
+        self.map.shift_remove(id)
+    }
+
+    /// Modify an existing prefab in place
+    ///
+    /// Returns false if there is no prefab with the given id.
+    pub fn update(&mut self, id: &str, f: impl FnOnce(&mut P)) -> bool {
+        match self.map.get_mut(id) {
+            Some(prefab) => {
+                f(prefab);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Iterate over all prefab ids
+    pub fn ids(&self) -> impl Iterator<Item = &PrefabId> {
+        self.map.keys()
+    }
+
+    /// Iterate over all prefabs
+    pub fn prefabs(&self) -> impl Iterator<Item = &P> {
+        self.map.values()
+    }
+
+    /// Iterate over all prefabs along with their ids
+    pub fn iter(&self) -> impl Iterator<Item = (&PrefabId, &P)> {
+        self.map.iter()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.map.contains_key(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Iterate over the prefabs that match a predicate
+    pub fn filter<'a>(
+        &'a self,
+        predicate: impl Fn(&P) -> bool + 'a,
+    ) -> impl Iterator<Item = (&'a PrefabId, &'a P)> + 'a {
+        self.map.iter().filter(move |(_, prefab)| predicate(prefab))
+    }
+
+    /// Randomly pick a prefab, with the chance of each prefab given by a weight function
+    ///
+    /// Prefabs with a weight of zero or less are never picked. Returns `None` if no prefab has a
+    /// positive weight.
+    pub fn choose_weighted(&self, weight: impl Fn(&P) -> f32) -> Option<(&PrefabId, &P)> {
+        let candidates: Vec<(&PrefabId, &P, f32)> = self
+            .map
+            .iter()
+            .map(|(id, prefab)| (id, prefab, weight(prefab)))
+            .filter(|(_, _, weight)| *weight > 0.0)
+            .collect();
+
+        candidates
+            .choose_weighted(&mut thread_rng(), |(_, _, weight)| *weight)
+            .ok()
+            .map(|(id, prefab, _)| (*id, *prefab))
+    }
+}
+
+impl<P: DeserializeOwned + Serialize> PrefabLib<P> {
+    /// Write prefabs to a pretty printed RON string
+    ///
+    /// The output can be loaded again with [PrefabLib::try_new]. Prefabs that were loaded with
+    /// `extends` are written with their parent again, along with the fields they set themselves
+    /// and any fields that no longer match the parent.
+    pub fn to_ron_string(&self) -> Result<String, PrefabError> {
+        let ron_string = ron::ser::to_string_pretty(self, PrettyConfig::new())?;
+        if self.inherited.is_empty() {
+            return Ok(ron_string);
+        }
+        Ok(inherit::write_inherited(&ron_string, &self.inherited)?)
+    }
+
+    /// Write prefabs to a RON file
+    pub fn save(&self, filepath: &str) -> Result<(), PrefabError> {
+        let ron_string = self.to_ron_string()?;
+        fs::write(Path::new(&filepath), ron_string).map_err(|source| PrefabError::Io {
+            path: filepath.to_owned(),
+            source,
+        })
+    }
+}
+
+impl<P: DeserializeOwned> Default for PrefabLib<P> {
+    fn default() -> Self {
+        PrefabLib {
+            map: PrefabMap::new(),
+            inherited: HashMap::new(),
+        }
+    }
+}
+
+impl<P: DeserializeOwned + Serialize> Serialize for PrefabLib<P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.map.len()))?;
+        for (id, prefab) in self.map.iter() {
+            map.serialize_entry(id, prefab)?;
+        }
+        map.end()
+    }
+}
+
+fn find_ron_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), PrefabError> {
+    let io_error = |source| PrefabError::Io {
+        path: dir.to_string_lossy().into_owned(),
+        source,
+    };
+
+    for entry in fs::read_dir(dir).map_err(io_error)? {
+        let path = entry.map_err(io_error)?.path();
+        if path.is_dir() {
+            find_ron_files(&path, files)?;
+        } else if path.extension() == Some(OsStr::new("ron")) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Parse a map of prefabs, keeping track of which prefab was being parsed if an error occurs
+fn parse_prefab_lib<P: DeserializeOwned>(
+    bytes: &[u8],
+    path: Option<&str>,
+) -> Result<PrefabLib<P>, PrefabError> {
+    if let Ok(src) = std::str::from_utf8(bytes) {
+        if inherit::uses_extends(src) {
+            if let Some(result) = inherit::parse_with_inheritance(src, path) {
+                return result;
+            }
+        }
+    }
+
+    let mut current_id = None;
+    let seed = PrefabMapSeed {
+        current_id: &mut current_id,
+        phantom: PhantomData,
+    };
+
+    let map = ron::Options::default()
+        .from_bytes_seed(bytes, seed)
+        .map_err(|e| PrefabError::Parse {
+            path: path.map(str::to_owned),
+            id: current_id,
+            line: e.position.line,
+            column: e.position.col,
+            msg: e.code.to_string(),
+        })?;
+    Ok(PrefabLib {
+        map,
+        inherited: HashMap::new(),
+    })
+}
+
+struct PrefabMapSeed<'a, P> {
+    current_id: &'a mut Option<PrefabId>,
+    phantom: PhantomData<P>,
+}
+
+impl<'de, 'a, P: DeserializeOwned> DeserializeSeed<'de> for PrefabMapSeed<'a, P> {
+    type Value = PrefabMap<P>;
+
+    fn deserialize<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, 'a, P: DeserializeOwned> Visitor<'de> for PrefabMapSeed<'a, P> {
+    type Value = PrefabMap<P>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of prefab ids to prefabs")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+        let mut map = PrefabMap::new();
+        while let Some(id) = access.next_key::<PrefabId>()? {
+            *self.current_id = Some(id.clone());
+            let prefab = access.next_value::<P>()?;
+            if map.insert(id.clone(), prefab).is_some() {
+                return Err(A::Error::custom(format!("duplicate prefab id '{}'", id)));
+            }
+        }
+        *self.current_id = None;
+        Ok(map)
+    }
 }
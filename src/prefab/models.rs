@@ -1,6 +1,7 @@
 //! Utility types for use in RON prefab files
 
 use bevy::prelude::*;
+use rand::{distributions::uniform::SampleUniform, thread_rng, Rng};
 use serde::Deserialize;
 
 #[derive(Deserialize, Clone, Copy)]
@@ -29,3 +30,144 @@ impl From<ColorRGBA> for Color {
         Color::rgba(c.r, c.g, c.b, c.a)
     }
 }
+
+/// Color written as a hex string such as `"#ff8800"`
+///
+/// Supports `rgb`, `rgba`, `rrggbb` and `rrggbbaa` with an optional leading `#`.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(try_from = "String")]
+pub struct ColorHex(Color);
+
+impl TryFrom<String> for ColorHex {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Color::hex(s.trim_start_matches('#'))
+            .map(ColorHex)
+            .map_err(|e| format!("invalid hex color '{}': {}", s, e))
+    }
+}
+
+impl From<ColorHex> for Color {
+    fn from(c: ColorHex) -> Self {
+        c.0
+    }
+}
+
+/// Hue is in degrees, saturation and lightness range from 0 to 1
+#[derive(Deserialize, Clone, Copy)]
+pub struct ColorHSL {
+    h: f32,
+    s: f32,
+    l: f32,
+}
+
+impl From<ColorHSL> for Color {
+    fn from(c: ColorHSL) -> Self {
+        Color::hsl(c.h, c.s, c.l)
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub struct ColorHSLA {
+    h: f32,
+    s: f32,
+    l: f32,
+    a: f32,
+}
+
+impl From<ColorHSLA> for Color {
+    fn from(c: ColorHSLA) -> Self {
+        Color::hsla(c.h, c.s, c.l, c.a)
+    }
+}
+
+/// Vector that can be written as `5.0`, `(1.0, 2.0)` or `(x: 1.0, y: 2.0)`
+#[derive(Deserialize, Clone, Copy)]
+#[serde(untagged)]
+pub enum Vector2 {
+    Splat(f32),
+    Tuple(f32, f32),
+    Struct { x: f32, y: f32 },
+}
+
+impl From<Vector2> for Vec2 {
+    fn from(v: Vector2) -> Self {
+        match v {
+            Vector2::Splat(v) => Vec2::splat(v),
+            Vector2::Tuple(x, y) | Vector2::Struct { x, y } => Vec2::new(x, y),
+        }
+    }
+}
+
+/// Vector that can be written as `5.0`, `(1.0, 2.0, 3.0)` or `(x: 1.0, y: 2.0, z: 3.0)`
+#[derive(Deserialize, Clone, Copy)]
+#[serde(untagged)]
+pub enum Vector3 {
+    Splat(f32),
+    Tuple(f32, f32, f32),
+    Struct { x: f32, y: f32, z: f32 },
+}
+
+impl From<Vector3> for Vec3 {
+    fn from(v: Vector3) -> Self {
+        match v {
+            Vector3::Splat(v) => Vec3::splat(v),
+            Vector3::Tuple(x, y, z) | Vector3::Struct { x, y, z } => Vec3::new(x, y, z),
+        }
+    }
+}
+
+/// Inclusive range of values to randomly pick from
+#[derive(Deserialize, Clone, Copy)]
+pub struct RandRange<T> {
+    pub min: T,
+    pub max: T,
+}
+
+impl<T: SampleUniform + PartialOrd + Copy> RandRange<T> {
+    /// Pick a random value within the range
+    ///
+    /// If `min` is not smaller than `max`, `min` is always returned.
+    pub fn sample(&self) -> T {
+        if self.min < self.max {
+            thread_rng().gen_range(self.min..=self.max)
+        } else {
+            self.min
+        }
+    }
+}
+
+/// Value that changes over time, defined by `(time, value)` keyframes
+///
+/// Values are linearly interpolated between keyframes and clamped outside of them.
+#[derive(Deserialize, Clone)]
+#[serde(from = "Vec<(f32, f32)>")]
+pub struct Curve {
+    keyframes: Vec<(f32, f32)>,
+}
+
+impl From<Vec<(f32, f32)>> for Curve {
+    fn from(mut keyframes: Vec<(f32, f32)>) -> Self {
+        keyframes.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Curve { keyframes }
+    }
+}
+
+impl Curve {
+    /// Get the value of the curve at a given time
+    ///
+    /// An empty curve always returns zero.
+    pub fn sample(&self, t: f32) -> f32 {
+        let next = self.keyframes.iter().position(|(time, _)| *time > t);
+        match next {
+            None => self.keyframes.last().map_or(0., |(_, v)| *v),
+            Some(0) => self.keyframes[0].1,
+            Some(i) => {
+                let (t0, v0) = self.keyframes[i - 1];
+                let (t1, v1) = self.keyframes[i];
+                v0 + (v1 - v0) * (t - t0) / (t1 - t0)
+            },
+        }
+    }
+}
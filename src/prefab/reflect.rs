@@ -0,0 +1,258 @@
+//! Prefabs made up of arbitrary reflected components
+//!
+//! Instead of deserializing into a single prefab struct, each prefab is a map of component type
+//! names to component values. Any component registered in the type registry with
+//! `#[reflect(Component, Deserialize)]` can be used.
+//!
+//! ```ron
+//! {
+//!     "goblin": {
+//!         "Health": (original_hp: 10, current_hp: 10, health_cap: None),
+//!         "DurationLifetime": 5.0,
+//!     },
+//! }
+//! ```
+//!
+//! Component types can be referred to by either their full type name or their short name.
+//!
+//! Requires the `serde` feature, which registers `ReflectDeserialize` for the components in this
+//! crate.
+
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+use bevy::{
+    ecs::system::{Command, EntityCommands},
+    prelude::*,
+    reflect::{ReflectDeserialize, TypeRegistry, TypeRegistryInternal},
+};
+use serde::de::{DeserializeSeed, Error, MapAccess, Visitor};
+
+use super::{spawn::SpawnPrefab, PrefabError, PrefabId};
+use crate::component::{
+    health::{
+        damage::{Armor, Resistances},
+        Health,
+    },
+    lifetime::DurationLifetime,
+};
+
+/// Prefab consisting of a list of reflected components
+pub struct ComponentPrefab {
+    components: Vec<Box<dyn Reflect>>,
+}
+
+impl ComponentPrefab {
+    /// Get the components that make up this prefab
+    pub fn components(&self) -> &[Box<dyn Reflect>] {
+        &self.components
+    }
+}
+
+impl SpawnPrefab for ComponentPrefab {
+    fn insert(&self, entity: &mut EntityCommands) {
+        let command = InsertReflected {
+            entity: entity.id(),
+            components: self.components.iter().map(|c| c.clone_value()).collect(),
+        };
+        entity.commands().add(command);
+    }
+}
+
+/// Library of component prefabs
+pub struct ReflectPrefabLib {
+    map: HashMap<PrefabId, ComponentPrefab>,
+}
+
+impl ReflectPrefabLib {
+    /// Parse component prefabs from a RON string
+    pub fn try_new(ron_string: &str, registry: &TypeRegistry) -> Result<Self, PrefabError> {
+        ReflectPrefabLib::parse(ron_string, None, registry)
+    }
+
+    /// Load component prefabs from a RON file
+    pub fn try_from_file(filepath: &str, registry: &TypeRegistry) -> Result<Self, PrefabError> {
+        let ron_string =
+            fs::read_to_string(Path::new(&filepath)).map_err(|source| PrefabError::Io {
+                path: filepath.to_owned(),
+                source,
+            })?;
+        ReflectPrefabLib::parse(&ron_string, Some(filepath), registry)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ComponentPrefab> {
+        self.map.get(id)
+    }
+
+    /// Spawn a new entity with all the components of a prefab
+    pub fn spawn(&self, cmd: &mut Commands, id: &str) -> Option<Entity> {
+        let prefab = self.get(id)?;
+        let mut entity = cmd.spawn();
+        prefab.insert(&mut entity);
+        Some(entity.id())
+    }
+
+    fn parse(
+        ron_string: &str,
+        path: Option<&str>,
+        registry: &TypeRegistry,
+    ) -> Result<Self, PrefabError> {
+        let registry = registry.read();
+        let mut current_id = None;
+        let seed = LibSeed {
+            registry: &registry,
+            current_id: &mut current_id,
+        };
+
+        let map = ron::Options::default()
+            .from_str_seed(ron_string, seed)
+            .map_err(|e| PrefabError::Parse {
+                path: path.map(str::to_owned),
+                id: current_id,
+                line: e.position.line,
+                column: e.position.col,
+                msg: e.code.to_string(),
+            })?;
+        Ok(ReflectPrefabLib { map })
+    }
+}
+
+/// Plugin to register the components in this crate so they can be used in component prefabs
+pub struct ReflectPrefabPlugin;
+
+impl Plugin for ReflectPrefabPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Health>()
+            .register_type::<Resistances>()
+            .register_type::<Armor>()
+            .register_type::<DurationLifetime>();
+
+        #[cfg(feature = "physics_2d")]
+        app.register_type::<crate::physics_2d::RigidBody>();
+    }
+}
+
+struct InsertReflected {
+    entity: Entity,
+    components: Vec<Box<dyn Reflect>>,
+}
+
+impl Command for InsertReflected {
+    fn write(self, world: &mut World) {
+        let registry = world.resource::<TypeRegistry>().clone();
+        let registry = registry.read();
+
+        for component in self.components.iter() {
+            let reflect_component = registry
+                .get_with_name(component.type_name())
+                .and_then(|registration| registration.data::<ReflectComponent>());
+            if let Some(reflect_component) = reflect_component {
+                reflect_component.insert(world, self.entity, &**component);
+            }
+        }
+    }
+}
+
+struct LibSeed<'a> {
+    registry: &'a TypeRegistryInternal,
+    current_id: &'a mut Option<PrefabId>,
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for LibSeed<'a> {
+    type Value = HashMap<PrefabId, ComponentPrefab>;
+
+    fn deserialize<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for LibSeed<'a> {
+    type Value = HashMap<PrefabId, ComponentPrefab>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of prefab ids to component prefabs")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+        let mut map = HashMap::new();
+        while let Some(id) = access.next_key::<PrefabId>()? {
+            *self.current_id = Some(id.clone());
+            let prefab = access.next_value_seed(PrefabSeed {
+                registry: self.registry,
+            })?;
+            if map.insert(id.clone(), prefab).is_some() {
+                return Err(A::Error::custom(format!("duplicate prefab id '{}'", id)));
+            }
+        }
+        *self.current_id = None;
+        Ok(map)
+    }
+}
+
+struct PrefabSeed<'a> {
+    registry: &'a TypeRegistryInternal,
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for PrefabSeed<'a> {
+    type Value = ComponentPrefab;
+
+    fn deserialize<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for PrefabSeed<'a> {
+    type Value = ComponentPrefab;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of component type names to components")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+        let mut components = vec![];
+        while let Some(type_name) = access.next_key::<String>()? {
+            let registration = self
+                .registry
+                .get_with_name(&type_name)
+                .or_else(|| self.registry.get_with_short_name(&type_name))
+                .ok_or_else(|| {
+                    A::Error::custom(format!("unknown component type '{}'", type_name))
+                })?;
+
+            if registration.data::<ReflectComponent>().is_none() {
+                return Err(A::Error::custom(format!(
+                    "type '{}' is not registered with #[reflect(Component)]",
+                    type_name
+                )));
+            }
+            let reflect_deserialize =
+                registration.data::<ReflectDeserialize>().ok_or_else(|| {
+                    A::Error::custom(format!(
+                        "type '{}' is not registered with #[reflect(Deserialize)]",
+                        type_name
+                    ))
+                })?;
+
+            components.push(access.next_value_seed(ComponentSeed(reflect_deserialize))?);
+        }
+        Ok(ComponentPrefab { components })
+    }
+}
+
+struct ComponentSeed<'a>(&'a ReflectDeserialize);
+
+impl<'de, 'a> DeserializeSeed<'de> for ComponentSeed<'a> {
+    type Value = Box<dyn Reflect>;
+
+    fn deserialize<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        self.0.deserialize(deserializer)
+    }
+}
@@ -0,0 +1,69 @@
+//! Spawn entities directly from prefabs
+//!
+//! Implement [SpawnPrefab] for a prefab type to describe which components it should create, then
+//! use [SpawnPrefabExt::spawn_prefab] on `Commands`.
+
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use serde::de::DeserializeOwned;
+
+use super::{PrefabId, PrefabLib};
+
+/// Prefab types that know how to insert their components onto an entity
+pub trait SpawnPrefab {
+    /// Insert the components described by this prefab
+    fn insert(&self, entity: &mut EntityCommands);
+
+    /// Ids of prefabs in the same library to spawn as children of this prefab
+    fn children(&self) -> Vec<PrefabId> {
+        vec![]
+    }
+}
+
+/// Extension trait to spawn prefabs using `Commands`
+pub trait SpawnPrefabExt {
+    /// Spawn a prefab and all of its children
+    ///
+    /// Returns `None` if the prefab does not exist. Missing children are skipped with a warning.
+    fn spawn_prefab<P>(&mut self, lib: &PrefabLib<P>, id: &str) -> Option<Entity>
+    where
+        P: SpawnPrefab + DeserializeOwned;
+}
+
+impl<'w, 's> SpawnPrefabExt for Commands<'w, 's> {
+    fn spawn_prefab<P>(&mut self, lib: &PrefabLib<P>, id: &str) -> Option<Entity>
+    where
+        P: SpawnPrefab + DeserializeOwned,
+    {
+        spawn_recursive(self, lib, id, &mut vec![])
+    }
+}
+
+fn spawn_recursive<P: SpawnPrefab + DeserializeOwned>(
+    cmd: &mut Commands,
+    lib: &PrefabLib<P>,
+    id: &str,
+    ancestors: &mut Vec<PrefabId>,
+) -> Option<Entity> {
+    let prefab = lib.get(id)?;
+
+    let mut entity = cmd.spawn();
+    prefab.insert(&mut entity);
+    let parent = entity.id();
+
+    ancestors.push(id.to_owned());
+    for child_id in prefab.children() {
+        if ancestors.contains(&child_id) {
+            warn!("prefab '{}' can not contain itself as a child", child_id);
+            continue;
+        }
+        match spawn_recursive(cmd, lib, &child_id, ancestors) {
+            Some(child) => {
+                cmd.entity(parent).add_child(child);
+            },
+            None => warn!("child prefab '{}' of '{}' does not exist", child_id, id),
+        }
+    }
+    ancestors.pop();
+
+    Some(parent)
+}
@@ -0,0 +1,63 @@
+//! Query prefabs by tag
+//!
+//! Prefab types that have a list of tags can implement [Tagged] so spawners can ask for something
+//! like "any tier-2 enemy" instead of hardcoding prefab ids.
+//!
+//! ```ignore
+//! #[derive(Deserialize)]
+//! struct EnemyPrefab {
+//!     tags: Vec<String>,
+//!     spawn_weight: f32,
+//! }
+//!
+//! impl Tagged for EnemyPrefab {
+//!     fn tags(&self) -> &[String] {
+//!         &self.tags
+//!     }
+//!
+//!     fn weight(&self) -> f32 {
+//!         self.spawn_weight
+//!     }
+//! }
+//!
+//! let (id, enemy) = lib.choose_with_tag("tier-2").unwrap();
+//! ```
+
+use serde::de::DeserializeOwned;
+
+use super::{PrefabId, PrefabLib};
+
+/// Prefab types that can be grouped by tags
+pub trait Tagged {
+    fn tags(&self) -> &[String];
+
+    /// Relative chance of this prefab being picked by [PrefabLib::choose_with_tag]
+    fn weight(&self) -> f32 {
+        1.0
+    }
+
+    fn has_tag(&self, tag: &str) -> bool {
+        self.tags().iter().any(|t| t == tag)
+    }
+}
+
+impl<P: DeserializeOwned + Tagged> PrefabLib<P> {
+    /// Iterate over the prefabs that have a tag
+    pub fn with_tag<'a>(
+        &'a self,
+        tag: &'a str,
+    ) -> impl Iterator<Item = (&'a PrefabId, &'a P)> + 'a {
+        self.filter(move |prefab| prefab.has_tag(tag))
+    }
+
+    /// Randomly pick a prefab that has a tag, based on the weight of each prefab
+    pub fn choose_with_tag(&self, tag: &str) -> Option<(&PrefabId, &P)> {
+        self.choose_weighted(|prefab| {
+            if prefab.has_tag(tag) {
+                prefab.weight()
+            } else {
+                0.0
+            }
+        })
+    }
+}
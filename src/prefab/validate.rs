@@ -0,0 +1,292 @@
+//! Validate prefab content before it is used at runtime
+//!
+//! Prefab types implement [Validate] to check their fields and cross references to other prefabs
+//! or assets. All problems are collected into a [ValidationContext] so they can be reported at
+//! once.
+//!
+//! [check_files] loads and validates a set of prefab files in one go:
+//!
+//! ```ignore
+//! for error in check_files::<MyPrefab, _>(&["assets/enemies.ron"], &[], Some("assets")) {
+//!     eprintln!("error: {}", error);
+//! }
+//! ```
+//!
+//! The `bobs-prefab-check` binary does the same for [DynamicPrefab] from the command line.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    path::PathBuf,
+};
+
+use serde::de::{DeserializeOwned, IgnoredAny};
+
+use super::{PrefabId, PrefabLib};
+
+/// Prefab types that can check their own content
+pub trait Validate {
+    /// Report any problems with this prefab to the context
+    fn validate(&self, ctx: &mut ValidationContext);
+}
+
+/// Problem found in a prefab
+#[derive(Debug, Clone)]
+pub struct ValidationError {
+    /// File the prefab was loaded from, if any
+    pub path: Option<String>,
+    /// Prefab with the problem, `None` for files that failed to load
+    pub id: Option<PrefabId>,
+    pub msg: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}: ", path)?;
+        }
+        if let Some(id) = &self.id {
+            write!(f, "prefab '{}': ", id)?;
+        }
+        write!(f, "{}", self.msg)
+    }
+}
+
+/// Collects validation errors along with the information needed to check cross references
+#[derive(Default)]
+pub struct ValidationContext {
+    known_ids: HashSet<PrefabId>,
+    asset_dir: Option<PathBuf>,
+    current_path: Option<String>,
+    current_id: PrefabId,
+    errors: Vec<ValidationError>,
+}
+
+impl ValidationContext {
+    pub fn new() -> Self {
+        ValidationContext::default()
+    }
+
+    /// Check sound paths against an asset directory
+    ///
+    /// If no asset directory is given, sound paths are not checked.
+    pub fn with_asset_dir(mut self, asset_dir: &str) -> Self {
+        self.asset_dir = Some(PathBuf::from(asset_dir));
+        self
+    }
+
+    /// Add ids that prefabs are allowed to reference
+    pub fn add_ids<'a>(&mut self, ids: impl IntoIterator<Item = &'a PrefabId>) {
+        self.known_ids.extend(ids.into_iter().cloned());
+    }
+
+    /// Validate every prefab in a library
+    pub fn validate_lib<P>(&mut self, lib: &PrefabLib<P>, path: Option<&str>)
+    where
+        P: Validate + DeserializeOwned,
+    {
+        self.current_path = path.map(str::to_owned);
+
+        let mut ids: Vec<&PrefabId> = lib.ids().collect();
+        ids.sort();
+        for id in ids {
+            self.current_id = id.clone();
+            if let Some(prefab) = lib.get(id) {
+                prefab.validate(self);
+            }
+        }
+    }
+
+    /// Report an error for the prefab currently being validated
+    pub fn error(&mut self, msg: impl Into<String>) {
+        self.errors.push(ValidationError {
+            path: self.current_path.clone(),
+            id: Some(self.current_id.clone()),
+            msg: msg.into(),
+        });
+    }
+
+    /// Check that a referenced prefab exists
+    pub fn check_prefab_ref(&mut self, id: &str) {
+        if !self.known_ids.contains(id) {
+            self.error(format!("references unknown prefab '{}'", id));
+        }
+    }
+
+    /// Check that all drops exist and have a valid probability
+    pub fn check_droptable(&mut self, drops: &[(PrefabId, f32)]) {
+        for (id, prob) in drops.iter() {
+            self.check_prefab_ref(id);
+            if !(0.0..=1.0).contains(prob) {
+                self.error(format!(
+                    "drop '{}' has probability {} outside of 0 to 1",
+                    id, prob
+                ));
+            }
+        }
+    }
+
+    /// Check that a wave spawn pool is not empty and only contains existing prefabs
+    pub fn check_spawn_pool(&mut self, spawn_pool: &[PrefabId]) {
+        if spawn_pool.is_empty() {
+            self.error("spawn pool is empty");
+        }
+        for id in spawn_pool.iter() {
+            self.check_prefab_ref(id);
+        }
+    }
+
+    /// Check that a sound file exists in the `sfx` folder of the asset directory
+    pub fn check_sound(&mut self, sound: &str) {
+        if let Some(asset_dir) = &self.asset_dir {
+            let path = asset_dir.join("sfx").join(sound);
+            if !path.is_file() {
+                self.error(format!("sound file '{}' does not exist", path.display()));
+            }
+        }
+    }
+
+    /// Get all errors collected so far
+    pub fn errors(&self) -> &[ValidationError] {
+        &self.errors
+    }
+
+    /// Finish validation, returning all of the collected errors if there were any
+    pub fn into_result(self) -> Result<(), Vec<ValidationError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+/// Prefab of any shape that is validated by convention
+///
+/// Fields anywhere in the prefab are checked based on their name:
+/// - `drops` or `droptable`: list of `(prefab_id, probability)`
+/// - `spawn_pool`: list of prefab ids
+/// - `sound` or `sfx`: sound path, `sounds`: list of sound paths
+pub struct DynamicPrefab(pub ron::Value);
+
+impl<'de> serde::Deserialize<'de> for DynamicPrefab {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <ron::Value as serde::Deserialize>::deserialize(deserializer).map(DynamicPrefab)
+    }
+}
+
+impl Validate for DynamicPrefab {
+    fn validate(&self, ctx: &mut ValidationContext) {
+        validate_value(&self.0, ctx);
+    }
+}
+
+fn validate_value(value: &ron::Value, ctx: &mut ValidationContext) {
+    use ron::Value;
+
+    match value {
+        Value::Map(map) => {
+            for (key, value) in map.iter() {
+                let field = match key {
+                    Value::String(field) => field.as_str(),
+                    _ => "",
+                };
+                match (field, value) {
+                    ("drops" | "droptable", Value::Seq(drops)) => {
+                        let drops: Option<Vec<(PrefabId, f32)>> = drops
+                            .iter()
+                            .map(|drop| drop.clone().into_rust().ok())
+                            .collect();
+                        match drops {
+                            Some(drops) => ctx.check_droptable(&drops),
+                            None => ctx
+                                .error(format!("'{}' must be a list of (id, probability)", field)),
+                        }
+                    },
+                    ("spawn_pool", value) => match value.clone().into_rust::<Vec<PrefabId>>() {
+                        Ok(spawn_pool) => ctx.check_spawn_pool(&spawn_pool),
+                        Err(_) => ctx.error("'spawn_pool' must be a list of prefab ids"),
+                    },
+                    ("sound" | "sfx", Value::String(sound)) => ctx.check_sound(sound),
+                    ("sounds", Value::Seq(sounds)) => {
+                        for sound in sounds.iter() {
+                            match sound {
+                                Value::String(sound) => ctx.check_sound(sound),
+                                _ => ctx.error("'sounds' must be a list of sound paths"),
+                            }
+                        }
+                    },
+                    (_, value) => validate_value(value, ctx),
+                }
+            }
+        },
+        Value::Seq(values) => {
+            for value in values.iter() {
+                validate_value(value, ctx);
+            }
+        },
+        Value::Option(Some(value)) => validate_value(value, ctx),
+        _ => {},
+    }
+}
+
+/// Load and validate prefab files against each other
+///
+/// Prefabs may reference any id in `files` or `ref_files`. Sound paths are checked against
+/// `asset_dir` if given. Files that fail to load and ids defined in more than one file are
+/// reported as errors too.
+pub fn check_files<P, S>(
+    files: &[S],
+    ref_files: &[S],
+    asset_dir: Option<&str>,
+) -> Vec<ValidationError>
+where
+    P: Validate + DeserializeOwned,
+    S: AsRef<str>,
+{
+    let mut errors = vec![];
+    let load_error = |e: super::PrefabError| ValidationError {
+        path: None,
+        id: None,
+        msg: e.to_string(),
+    };
+    let mut ctx = match asset_dir {
+        Some(asset_dir) => ValidationContext::new().with_asset_dir(asset_dir),
+        None => ValidationContext::new(),
+    };
+
+    for file in ref_files.iter() {
+        match PrefabLib::<IgnoredAny>::try_from_file(file.as_ref()) {
+            Ok(lib) => ctx.add_ids(lib.ids()),
+            Err(e) => errors.push(load_error(e)),
+        }
+    }
+
+    let mut libs = vec![];
+    let mut seen: HashMap<PrefabId, &str> = HashMap::new();
+    for file in files.iter() {
+        let file = file.as_ref();
+        match PrefabLib::<P>::try_from_file(file) {
+            Ok(lib) => {
+                for id in lib.ids() {
+                    if let Some(other) = seen.insert(id.clone(), file) {
+                        errors.push(ValidationError {
+                            path: Some(file.to_owned()),
+                            id: Some(id.clone()),
+                            msg: format!("duplicate id, also defined in {}", other),
+                        });
+                    }
+                }
+                ctx.add_ids(lib.ids());
+                libs.push((file, lib));
+            },
+            Err(e) => errors.push(load_error(e)),
+        }
+    }
+
+    for (file, lib) in libs.iter() {
+        ctx.validate_lib(lib, Some(file));
+    }
+    errors.extend(ctx.errors().iter().cloned());
+    errors
+}